@@ -0,0 +1,162 @@
+use crate::query::{Aggregate, NeighbourCount, SymbolQuery};
+use crate::render::Format;
+
+const USAGE: &str = "usage: day3 [--input FILE] [COMMAND]
+
+commands:
+  (none)                  print the part 1 and part 2 answers
+  symbols [OPTIONS]       list symbols matching a query and the total of their aggregates
+      --kind C            only consider symbols of kind C (default: any)
+      --exactly N         symbol must touch exactly N numbers
+      --at-least N        symbol must touch at least N numbers
+      --agg sum|product|max
+                          how to combine each symbol's numbers (default: sum)
+  isolated                list numbers touching no symbols and their sum
+  render [--html]         print the schematic with part numbers and gears highlighted,
+                          as ANSI colours or as an HTML fragment";
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Command {
+    Solve,
+    Symbols {
+        query: SymbolQuery,
+        aggregate: Aggregate,
+    },
+    Isolated,
+    Render {
+        format: Format,
+    },
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Args {
+    pub input: String,
+    pub command: Command,
+}
+
+fn next_value<'a>(
+    flag: &str,
+    args: &mut impl Iterator<Item = &'a String>,
+) -> Result<&'a String, String> {
+    args.next()
+        .ok_or_else(|| format!("{} requires a value\n\n{}", flag, USAGE))
+}
+
+fn parse_count(flag: &str, value: &str) -> Result<usize, String> {
+    value
+        .parse()
+        .map_err(|_| format!("{} expects a number, got '{}'", flag, value))
+}
+
+// Parses the command line, excluding the program name.
+pub fn parse_args(args: &[String]) -> Result<Args, String> {
+    let mut input = String::from("input");
+    let mut command: Option<String> = None;
+    let mut kind: Option<char> = None;
+    let mut count = NeighbourCount::Any;
+    let mut aggregate = Aggregate::Sum;
+    let mut format = Format::Ansi;
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--input" => input = next_value(arg, &mut iter)?.clone(),
+            "--kind" => {
+                let value = next_value(arg, &mut iter)?;
+                let mut chars = value.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => kind = Some(c),
+                    _ => {
+                        return Err(format!(
+                            "--kind expects a single character, got '{}'",
+                            value
+                        ))
+                    }
+                }
+            }
+            "--exactly" => {
+                count = NeighbourCount::Exactly(parse_count(arg, next_value(arg, &mut iter)?)?)
+            }
+            "--at-least" => {
+                count = NeighbourCount::AtLeast(parse_count(arg, next_value(arg, &mut iter)?)?)
+            }
+            "--agg" => aggregate = next_value(arg, &mut iter)?.parse()?,
+            "--html" => format = Format::Html,
+            "-h" | "--help" => return Err(USAGE.to_string()),
+            _ if arg.starts_with('-') => {
+                return Err(format!("unknown option '{}'\n\n{}", arg, USAGE))
+            }
+            _ if command.is_none() => command = Some(arg.clone()),
+            _ => return Err(format!("unexpected argument '{}'\n\n{}", arg, USAGE)),
+        }
+    }
+
+    let command = match command.as_deref() {
+        None => Command::Solve,
+        Some("symbols") => Command::Symbols {
+            query: SymbolQuery { kind, count },
+            aggregate,
+        },
+        Some("isolated") => Command::Isolated,
+        Some("render") => Command::Render { format },
+        Some(other) => return Err(format!("unknown command '{}'\n\n{}", other, USAGE)),
+    };
+
+    Ok(Args { input, command })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|a| a.to_string()).collect()
+    }
+
+    #[test]
+    fn parse_args_test() {
+        assert_eq!(
+            parse_args(&[]).unwrap(),
+            Args {
+                input: String::from("input"),
+                command: Command::Solve
+            }
+        );
+
+        assert_eq!(
+            parse_args(&args(&[
+                "symbols",
+                "--kind",
+                "*",
+                "--exactly",
+                "2",
+                "--agg",
+                "product",
+                "--input",
+                "example"
+            ]))
+            .unwrap(),
+            Args {
+                input: String::from("example"),
+                command: Command::Symbols {
+                    query: SymbolQuery {
+                        kind: Some('*'),
+                        count: NeighbourCount::Exactly(2),
+                    },
+                    aggregate: Aggregate::Product,
+                }
+            }
+        );
+
+        assert_eq!(
+            parse_args(&args(&["render", "--html"])).unwrap().command,
+            Command::Render {
+                format: Format::Html
+            }
+        );
+
+        assert!(parse_args(&args(&["symbols", "--agg", "min"])).is_err());
+        assert!(parse_args(&args(&["symbols", "--kind", "**"])).is_err());
+        assert!(parse_args(&args(&["frobnicate"])).is_err());
+    }
+}
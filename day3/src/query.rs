@@ -0,0 +1,187 @@
+use crate::schematic::{Number, Schematic, Symbol};
+use std::collections::HashSet;
+
+// How many distinct numbers a symbol must touch to match a query.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NeighbourCount {
+    Any,
+    Exactly(usize),
+    AtLeast(usize),
+}
+
+impl NeighbourCount {
+    pub fn matches(&self, count: usize) -> bool {
+        match self {
+            NeighbourCount::Any => true,
+            NeighbourCount::Exactly(n) => count == *n,
+            NeighbourCount::AtLeast(n) => count >= *n,
+        }
+    }
+}
+
+// How the numbers touching a matched symbol are combined into a single value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Aggregate {
+    Sum,
+    Product,
+    Max,
+}
+
+impl Aggregate {
+    pub fn apply(&self, numbers: &[&Number]) -> u64 {
+        let values = numbers.iter().map(|n| n.number as u64);
+        match self {
+            Aggregate::Sum => values.sum(),
+            Aggregate::Product => values.product(),
+            Aggregate::Max => values.max().unwrap_or(0),
+        }
+    }
+}
+
+impl std::str::FromStr for Aggregate {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "sum" => Ok(Aggregate::Sum),
+            "product" => Ok(Aggregate::Product),
+            "max" => Ok(Aggregate::Max),
+            _ => Err(format!(
+                "unknown aggregate '{}', expected sum, product or max",
+                s
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SymbolQuery {
+    // None matches any symbol.
+    pub kind: Option<char>,
+    pub count: NeighbourCount,
+}
+
+#[derive(Debug)]
+pub struct SymbolMatch<'a> {
+    pub symbol: &'a Symbol,
+    pub numbers: Vec<&'a Number>,
+}
+
+impl SymbolQuery {
+    pub fn run<'a>(&self, schematic: &'a Schematic) -> Vec<SymbolMatch<'a>> {
+        schematic
+            .symbols()
+            .iter()
+            .filter(|symbol| self.kind.is_none_or(|kind| symbol.kind == kind))
+            .map(|symbol| SymbolMatch {
+                symbol,
+                numbers: schematic
+                    .adjacent_numbers(symbol)
+                    .into_iter()
+                    .map(|index| &schematic.numbers()[index])
+                    .collect(),
+            })
+            .filter(|m| self.count.matches(m.numbers.len()))
+            .collect()
+    }
+
+    // Aggregates the numbers around each matching symbol and sums the results.
+    pub fn total(&self, schematic: &Schematic, aggregate: Aggregate) -> u64 {
+        self.run(schematic)
+            .iter()
+            .map(|m| aggregate.apply(&m.numbers))
+            .sum()
+    }
+}
+
+fn touched_numbers(schematic: &Schematic) -> HashSet<usize> {
+    schematic
+        .symbols()
+        .iter()
+        .flat_map(|symbol| schematic.adjacent_numbers(symbol))
+        .collect()
+}
+
+// Numbers touching at least one symbol of any kind, in schematic order.
+pub fn part_numbers(schematic: &Schematic) -> Vec<&Number> {
+    let touched = touched_numbers(schematic);
+    schematic
+        .numbers()
+        .iter()
+        .enumerate()
+        .filter(|(index, _)| touched.contains(index))
+        .map(|(_, num)| num)
+        .collect()
+}
+
+// Numbers touching no symbols at all, in schematic order.
+pub fn isolated_numbers(schematic: &Schematic) -> Vec<&Number> {
+    let touched = touched_numbers(schematic);
+    schematic
+        .numbers()
+        .iter()
+        .enumerate()
+        .filter(|(index, _)| !touched.contains(index))
+        .map(|(_, num)| num)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn example() -> Schematic {
+        let lines: Vec<String> = [
+            "467..114..",
+            "...*......",
+            "..35..633.",
+            "......#...",
+            "617*......",
+            ".....+.58.",
+            "..592.....",
+            "......755.",
+            "...$.*....",
+            ".664.598..",
+        ]
+        .iter()
+        .map(|l| l.to_string())
+        .collect();
+        Schematic::parse(&lines)
+    }
+
+    #[test]
+    fn symbol_query_test() {
+        let schematic = example();
+
+        let at_least_one = SymbolQuery {
+            kind: Some('*'),
+            count: NeighbourCount::AtLeast(1),
+        };
+        assert_eq!(at_least_one.run(&schematic).len(), 3);
+        assert_eq!(
+            at_least_one.total(&schematic, Aggregate::Max),
+            467 + 617 + 755
+        );
+
+        let any_symbol = SymbolQuery {
+            kind: None,
+            count: NeighbourCount::Exactly(1),
+        };
+        assert_eq!(
+            any_symbol.total(&schematic, Aggregate::Sum),
+            617 + 633 + 592 + 664
+        );
+    }
+
+    #[test]
+    fn isolated_numbers_test() {
+        let schematic = example();
+
+        let isolated: Vec<u32> = isolated_numbers(&schematic)
+            .iter()
+            .map(|n| n.number)
+            .collect();
+        assert_eq!(isolated, vec![114, 58]);
+        assert_eq!(part_numbers(&schematic).len(), 8);
+    }
+}
@@ -0,0 +1,226 @@
+use crate::query::{self, Aggregate, NeighbourCount, SymbolQuery};
+use crate::schematic::{self, Schematic};
+use std::collections::{HashMap, HashSet};
+
+const ANSI_RESET: &str = "\x1b[0m";
+const ANSI_PART: &str = "\x1b[32m";
+const ANSI_NON_PART: &str = "\x1b[2m";
+const ANSI_GEAR: &str = "\x1b[1;33m";
+const ANSI_SYMBOL: &str = "\x1b[1m";
+
+const HTML_STYLE: &str = "<style>
+.schematic { font-family: monospace; background: #111; color: #ccc; }
+.part { color: #4c4; }
+.non-part { color: #555; }
+.gear { color: #ff0; font-weight: bold; }
+.symbol { color: #fff; font-weight: bold; }
+</style>";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Ansi,
+    Html,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Class {
+    Plain,
+    Part,
+    NonPart,
+    Gear,
+    Symbol,
+}
+
+impl Class {
+    fn ansi(&self) -> Option<&'static str> {
+        match self {
+            Class::Plain => None,
+            Class::Part => Some(ANSI_PART),
+            Class::NonPart => Some(ANSI_NON_PART),
+            Class::Gear => Some(ANSI_GEAR),
+            Class::Symbol => Some(ANSI_SYMBOL),
+        }
+    }
+
+    fn html(&self) -> Option<&'static str> {
+        match self {
+            Class::Plain => None,
+            Class::Part => Some("part"),
+            Class::NonPart => Some("non-part"),
+            Class::Gear => Some("gear"),
+            Class::Symbol => Some("symbol"),
+        }
+    }
+}
+
+fn escape_html(c: char) -> String {
+    match c {
+        '<' => String::from("&lt;"),
+        '>' => String::from("&gt;"),
+        '&' => String::from("&amp;"),
+        '"' => String::from("&quot;"),
+        _ => c.to_string(),
+    }
+}
+
+// Renders the schematic with part numbers, non-part numbers and gears picked out, followed on each
+// row by the ratios of any gears on it.
+pub fn render(lines: &[String], schematic: &Schematic, format: Format) -> String {
+    let parts: HashSet<(isize, isize)> = query::part_numbers(schematic)
+        .iter()
+        .map(|n| (n.row, n.start_col))
+        .collect();
+
+    let gear_query = SymbolQuery {
+        kind: Some('*'),
+        count: NeighbourCount::Exactly(2),
+    };
+    let gears: HashMap<(isize, isize), u64> = gear_query
+        .run(schematic)
+        .iter()
+        .map(|m| {
+            (
+                (m.symbol.row, m.symbol.col),
+                Aggregate::Product.apply(&m.numbers),
+            )
+        })
+        .collect();
+    let symbols: HashSet<(isize, isize)> =
+        schematic.symbols().iter().map(|s| (s.row, s.col)).collect();
+
+    let classify = |row: isize, col: isize| -> Class {
+        if let Some(index) = schematic.number_at(row, col) {
+            let num = &schematic.numbers()[index];
+            if parts.contains(&(num.row, num.start_col)) {
+                Class::Part
+            } else {
+                Class::NonPart
+            }
+        } else if gears.contains_key(&(row, col)) {
+            Class::Gear
+        } else if symbols.contains(&(row, col)) {
+            Class::Symbol
+        } else {
+            Class::Plain
+        }
+    };
+
+    let mut out = String::new();
+    if format == Format::Html {
+        out.push_str(HTML_STYLE);
+        out.push_str("\n<pre class=\"schematic\">\n");
+    }
+
+    for (row, line) in lines.iter().enumerate() {
+        let row = row as isize;
+        let mut current = Class::Plain;
+        let mut row_gears: Vec<(isize, u64)> = Vec::new();
+
+        for (col, c) in schematic::trim_line_ending(line).chars().enumerate() {
+            let col = col as isize;
+            let class = classify(row, col);
+            if let Some(ratio) = gears.get(&(row, col)) {
+                row_gears.push((col, *ratio));
+            }
+
+            // Gears are always wrapped individually so HTML output can carry the ratio on hover.
+            if class != current || class == Class::Gear {
+                close(&mut out, format, current);
+                open(&mut out, format, class, gears.get(&(row, col)));
+                current = class;
+            }
+
+            match format {
+                Format::Ansi => out.push(c),
+                Format::Html => out.push_str(&escape_html(c)),
+            }
+        }
+        close(&mut out, format, current);
+
+        if !row_gears.is_empty() {
+            let ratios: Vec<String> = row_gears
+                .iter()
+                .map(|(col, ratio)| format!("col {}: {}", col, ratio))
+                .collect();
+            out.push_str(&format!("   gears {}", ratios.join(", ")));
+        }
+        out.push('\n');
+    }
+
+    if format == Format::Html {
+        out.push_str("</pre>\n");
+    }
+
+    out
+}
+
+fn open(out: &mut String, format: Format, class: Class, ratio: Option<&u64>) {
+    match format {
+        Format::Ansi => {
+            if let Some(code) = class.ansi() {
+                out.push_str(code);
+            }
+        }
+        Format::Html => {
+            if let Some(name) = class.html() {
+                match ratio {
+                    Some(ratio) => out.push_str(&format!(
+                        "<span class=\"{}\" title=\"ratio {}\">",
+                        name, ratio
+                    )),
+                    None => out.push_str(&format!("<span class=\"{}\">", name)),
+                }
+            }
+        }
+    }
+}
+
+fn close(out: &mut String, format: Format, class: Class) {
+    if class == Class::Plain {
+        return;
+    }
+
+    match format {
+        Format::Ansi => out.push_str(ANSI_RESET),
+        Format::Html => out.push_str("</span>"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines() -> Vec<String> {
+        vec![
+            String::from("467..114.."),
+            String::from("...*......"),
+            String::from("..35.&633."),
+        ]
+    }
+
+    #[test]
+    fn render_ansi_test() {
+        let lines = lines();
+        let schematic = Schematic::parse(&lines);
+        let rendered = render(&lines, &schematic, Format::Ansi);
+        let rows: Vec<&str> = rendered.lines().collect();
+
+        assert_eq!(rows[0], "\x1b[32m467\x1b[0m..\x1b[2m114\x1b[0m..");
+        assert_eq!(rows[1], "...\x1b[1;33m*\x1b[0m......   gears col 3: 16345");
+        assert_eq!(
+            rows[2],
+            "..\x1b[32m35\x1b[0m.\x1b[1m&\x1b[0m\x1b[32m633\x1b[0m."
+        );
+    }
+
+    #[test]
+    fn render_html_test() {
+        let lines = lines();
+        let schematic = Schematic::parse(&lines);
+        let rendered = render(&lines, &schematic, Format::Html);
+
+        assert!(rendered.contains("<span class=\"gear\" title=\"ratio 16345\">*</span>"));
+        assert!(rendered.contains("<span class=\"non-part\">114</span>"));
+        assert!(rendered.contains("<span class=\"symbol\">&amp;</span>"));
+    }
+}
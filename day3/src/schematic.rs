@@ -0,0 +1,209 @@
+use std::collections::HashMap;
+
+// Columns are counted in chars rather than bytes, so multibyte symbols occupy a single column.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Number {
+    pub number: u32,
+    pub row: isize,
+    pub start_col: isize,
+    pub end_col: isize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Symbol {
+    pub kind: char,
+    pub row: isize,
+    pub col: isize,
+}
+
+// The parsed engine schematic. Numbers and symbols are stored once, and every cell covered by a
+// number maps back to that number's index so adjacency lookups don't need to scan the whole list.
+#[derive(Debug, Default)]
+pub struct Schematic {
+    numbers: Vec<Number>,
+    symbols: Vec<Symbol>,
+    number_index: HashMap<(isize, isize), usize>,
+}
+
+// Strips a trailing "\n" or "\r\n", so CRLF input parses the same as LF input.
+pub fn trim_line_ending(line: &str) -> &str {
+    let line = line.strip_suffix('\n').unwrap_or(line);
+    line.strip_suffix('\r').unwrap_or(line)
+}
+
+impl Schematic {
+    pub fn parse(lines: &[String]) -> Schematic {
+        let mut schematic = Schematic::default();
+
+        for (row, line) in lines.iter().enumerate() {
+            let row = row as isize;
+            let mut cur_num: Option<Number> = None;
+
+            for (col, c) in trim_line_ending(line).chars().enumerate() {
+                let col = col as isize;
+                if let Some(digit) = c.to_digit(10) {
+                    // Start or continue a number.
+                    let num = cur_num.get_or_insert(Number {
+                        number: 0,
+                        row,
+                        start_col: col,
+                        end_col: col,
+                    });
+                    num.number = num.number * 10 + digit;
+                    num.end_col = col;
+                    continue;
+                }
+
+                // Not a digit, so any number in progress has ended.
+                if let Some(num) = cur_num.take() {
+                    schematic.add_number(num);
+                }
+
+                if c != '.' {
+                    schematic.symbols.push(Symbol { kind: c, row, col });
+                }
+            }
+
+            // End of the line, check if we still need to terminate a number.
+            if let Some(num) = cur_num.take() {
+                schematic.add_number(num);
+            }
+        }
+
+        schematic
+    }
+
+    fn add_number(&mut self, num: Number) {
+        let index = self.numbers.len();
+        for col in num.start_col..=num.end_col {
+            self.number_index.insert((num.row, col), index);
+        }
+        self.numbers.push(num);
+    }
+
+    pub fn numbers(&self) -> &[Number] {
+        &self.numbers
+    }
+
+    pub fn symbols(&self) -> &[Symbol] {
+        &self.symbols
+    }
+
+    pub fn number_at(&self, row: isize, col: isize) -> Option<usize> {
+        self.number_index.get(&(row, col)).copied()
+    }
+
+    // Indices of the distinct numbers in the 8 cells surrounding the given symbol, in the order
+    // they are first encountered.
+    pub fn adjacent_numbers(&self, symbol: &Symbol) -> Vec<usize> {
+        let mut result: Vec<usize> = Vec::new();
+        for row in (symbol.row - 1)..=(symbol.row + 1) {
+            for col in (symbol.col - 1)..=(symbol.col + 1) {
+                if let Some(index) = self.number_at(row, col) {
+                    // A number spans at most 3 neighbouring cells, so a linear check is cheap.
+                    if !result.contains(&index) {
+                        result.push(index);
+                    }
+                }
+            }
+        }
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_test() {
+        let lines = vec![String::from("467..114.."), String::from("...*......")];
+        let schematic = Schematic::parse(&lines);
+
+        assert_eq!(
+            schematic.numbers(),
+            &[
+                Number {
+                    number: 467,
+                    row: 0,
+                    start_col: 0,
+                    end_col: 2
+                },
+                Number {
+                    number: 114,
+                    row: 0,
+                    start_col: 5,
+                    end_col: 7
+                },
+            ]
+        );
+        assert_eq!(
+            schematic.symbols(),
+            &[Symbol {
+                kind: '*',
+                row: 1,
+                col: 3
+            }]
+        );
+        assert_eq!(schematic.number_at(0, 1), Some(0));
+        assert_eq!(schematic.number_at(0, 3), None);
+        assert_eq!(schematic.adjacent_numbers(&schematic.symbols()[0]), vec![0]);
+    }
+
+    #[test]
+    fn number_at_line_end_test() {
+        let lines = vec![String::from("..*12"), String::from("34...")];
+        let schematic = Schematic::parse(&lines);
+
+        assert_eq!(schematic.numbers()[0].end_col, 4);
+        assert_eq!(schematic.numbers()[1].start_col, 0);
+        assert_eq!(
+            schematic.adjacent_numbers(&schematic.symbols()[0]),
+            vec![0, 1]
+        );
+    }
+
+    #[test]
+    fn multibyte_symbol_test() {
+        let lines = vec![
+            String::from("€12..7"),
+            String::from("..§.é."),
+            String::from("345→.."),
+        ];
+        let schematic = Schematic::parse(&lines);
+
+        let spans: Vec<(u32, isize, isize)> = schematic
+            .numbers()
+            .iter()
+            .map(|n| (n.number, n.start_col, n.end_col))
+            .collect();
+        assert_eq!(spans, vec![(12, 1, 2), (7, 5, 5), (345, 0, 2)]);
+
+        let kinds: Vec<(char, isize)> = schematic
+            .symbols()
+            .iter()
+            .map(|s| (s.kind, s.col))
+            .collect();
+        assert_eq!(kinds, vec![('€', 0), ('§', 2), ('é', 4), ('→', 3)]);
+
+        // '§' touches 12 above and 345 below, 'é' touches 7 but not 345.
+        assert_eq!(
+            schematic.adjacent_numbers(&schematic.symbols()[1]),
+            vec![0, 2]
+        );
+        assert_eq!(schematic.adjacent_numbers(&schematic.symbols()[2]), vec![1]);
+        assert_eq!(schematic.adjacent_numbers(&schematic.symbols()[3]), vec![2]);
+    }
+
+    #[test]
+    fn crlf_test() {
+        let lf = vec![String::from("12*.."), String::from("..34.")];
+        let crlf = vec![String::from("12*..\r"), String::from("..34.\r\n")];
+
+        let lf = Schematic::parse(&lf);
+        let crlf = Schematic::parse(&crlf);
+        assert_eq!(lf.numbers(), crlf.numbers());
+        assert_eq!(lf.symbols(), crlf.symbols());
+    }
+}
@@ -0,0 +1,159 @@
+use crate::scratchcard::Scratchcard;
+use std::fmt;
+use std::ops::Range;
+
+// What to do when a card's matches would win copies of cards beyond the last one in the table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PastEndPolicy {
+    // Only award copies of the cards that exist.
+    Clamp,
+    // Treat the input as invalid.
+    Error,
+}
+
+impl std::str::FromStr for PastEndPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "clamp" => Ok(PastEndPolicy::Clamp),
+            "error" => Ok(PastEndPolicy::Error),
+            _ => Err(format!(
+                "unknown past-end policy '{}', expected clamp or error",
+                s
+            )),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum CascadeError {
+    PastLastCard { card: u32, matches: u32, last: u32 },
+    Overflow { card: u32 },
+}
+
+impl fmt::Display for CascadeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CascadeError::PastLastCard {
+                card,
+                matches,
+                last,
+            } => write!(
+                f,
+                "card {} has {} matches, which wins copies past the last card ({})",
+                card, matches, last
+            ),
+            CascadeError::Overflow { card } => {
+                write!(f, "a total overflowed while processing card {}", card)
+            }
+        }
+    }
+}
+
+// Indices of the cards that the card at index i wins copies of.
+pub fn won_cards(
+    cards: &[Scratchcard],
+    i: usize,
+    policy: PastEndPolicy,
+) -> Result<Range<usize>, CascadeError> {
+    let card = &cards[i];
+    let end = i + 1 + card.matches() as usize;
+    if end > cards.len() {
+        match policy {
+            PastEndPolicy::Clamp => return Ok((i + 1)..cards.len()),
+            PastEndPolicy::Error => {
+                return Err(CascadeError::PastLastCard {
+                    card: card.id,
+                    matches: card.matches(),
+                    last: cards[cards.len() - 1].id,
+                })
+            }
+        }
+    }
+
+    Ok((i + 1)..end)
+}
+
+// Returns how many instances (original plus copies) of each card are held once the cascade has
+// finished.
+pub fn card_counts(
+    cards: &[Scratchcard],
+    policy: PastEndPolicy,
+) -> Result<Vec<u128>, CascadeError> {
+    let mut card_counts = vec![1u128; cards.len()];
+    for i in 0..cards.len() {
+        for j in won_cards(cards, i, policy)? {
+            card_counts[j] = card_counts[j]
+                .checked_add(card_counts[i])
+                .ok_or(CascadeError::Overflow { card: cards[i].id })?;
+        }
+    }
+
+    Ok(card_counts)
+}
+
+pub fn total_cards(cards: &[Scratchcard], policy: PastEndPolicy) -> Result<u128, CascadeError> {
+    let counts = card_counts(cards, policy)?;
+    counts
+        .iter()
+        .zip(cards)
+        .try_fold(0u128, |total, (count, card)| {
+            total
+                .checked_add(*count)
+                .ok_or(CascadeError::Overflow { card: card.id })
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::numberset::NumberSet;
+
+    // Builds n cards where card i (1-based) wins `matches(i)` copies.
+    fn cards_with_matches(n: u32, matches: impl Fn(u32) -> u32) -> Vec<Scratchcard> {
+        (1..=n)
+            .map(|id| {
+                let mut nums = NumberSet::default();
+                for n in 0..matches(id) {
+                    nums.insert(n);
+                }
+                Scratchcard::new(id, nums.clone(), nums)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn past_end_test() {
+        // Card 2 of 3 has 5 matches, but only card 3 exists after it.
+        let cards = cards_with_matches(3, |id| if id == 2 { 5 } else { 0 });
+
+        assert_eq!(card_counts(&cards, PastEndPolicy::Clamp), Ok(vec![1, 1, 2]));
+        assert_eq!(
+            card_counts(&cards, PastEndPolicy::Error),
+            Err(CascadeError::PastLastCard {
+                card: 2,
+                matches: 5,
+                last: 3
+            })
+        );
+    }
+
+    #[test]
+    fn large_counts_test() {
+        // Each card wins a copy of every later card, so card i ends up with 2^(i-1) instances.
+        let cards = cards_with_matches(100, |id| 100 - id);
+        let counts = card_counts(&cards, PastEndPolicy::Error).unwrap();
+        assert_eq!(counts[99], 1u128 << 99);
+        assert_eq!(
+            total_cards(&cards, PastEndPolicy::Error),
+            Ok((1u128 << 100) - 1)
+        );
+
+        let cards = cards_with_matches(140, |id| u32::min(100, 140 - id));
+        assert!(matches!(
+            card_counts(&cards, PastEndPolicy::Error),
+            Err(CascadeError::Overflow { .. })
+        ));
+    }
+}
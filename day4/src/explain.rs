@@ -0,0 +1,187 @@
+use crate::cascade::{self, CascadeError, PastEndPolicy};
+use crate::scratchcard::Scratchcard;
+use std::fmt::Write;
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct CardExplanation {
+    pub id: u32,
+    pub matches: u32,
+    pub points: u128,
+    // Total instances held, including the original.
+    pub copies: u128,
+    // (card id, copies won from that card), in card order.
+    pub sources: Vec<(u32, u128)>,
+}
+
+// Replays the part 2 cascade, recording where each card's copies came from. Every instance of a
+// card wins one copy of each card in its range, so card i contributes its final count to each
+// card it wins.
+pub fn explain(
+    cards: &[Scratchcard],
+    policy: PastEndPolicy,
+) -> Result<Vec<CardExplanation>, CascadeError> {
+    let counts = cascade::card_counts(cards, policy)?;
+
+    let mut explanations: Vec<CardExplanation> = cards
+        .iter()
+        .zip(&counts)
+        .map(|(card, count)| {
+            Ok(CardExplanation {
+                id: card.id,
+                matches: card.matches(),
+                points: card
+                    .points()
+                    .ok_or(CascadeError::Overflow { card: card.id })?,
+                copies: *count,
+                sources: Vec::new(),
+            })
+        })
+        .collect::<Result<_, _>>()?;
+
+    for i in 0..cards.len() {
+        for j in cascade::won_cards(cards, i, policy)? {
+            explanations[j].sources.push((cards[i].id, counts[i]));
+        }
+    }
+
+    Ok(explanations)
+}
+
+pub fn format_table(explanations: &[CardExplanation]) -> Result<String, CascadeError> {
+    let mut out = String::new();
+    writeln!(
+        out,
+        "{:>6} {:>8} {:>8} {:>12}",
+        "Card", "Matches", "Points", "Copies"
+    )
+    .unwrap();
+    for e in explanations {
+        writeln!(
+            out,
+            "{:>6} {:>8} {:>8} {:>12}",
+            e.id, e.matches, e.points, e.copies
+        )
+        .unwrap();
+    }
+
+    let (mut total, mut points) = (0u128, 0u128);
+    for e in explanations {
+        total = total
+            .checked_add(e.copies)
+            .ok_or(CascadeError::Overflow { card: e.id })?;
+        points = points
+            .checked_add(e.points)
+            .ok_or(CascadeError::Overflow { card: e.id })?;
+    }
+    writeln!(out, "{:>6} {:>8} {:>8} {:>12}", "Total", "", points, total).unwrap();
+    out.push('\n');
+
+    for e in explanations {
+        let mut terms = vec![String::from("1 original")];
+        terms.extend(
+            e.sources
+                .iter()
+                .map(|(id, copies)| format!("{} from card {}", copies, id)),
+        );
+        writeln!(out, "Card {}: {} = {}", e.id, terms.join(" + "), e.copies).unwrap();
+    }
+
+    Ok(out)
+}
+
+// Exports the copy-propagation graph: one node per card, and an edge from each card to every card
+// it wins copies of, labelled with the number of copies that edge carries.
+pub fn format_dot(explanations: &[CardExplanation]) -> String {
+    let mut out = String::from("digraph cascade {\n    rankdir=LR;\n");
+    for e in explanations {
+        writeln!(
+            out,
+            "    card{} [label=\"Card {}\\n{} matches, {} copies\"];",
+            e.id, e.id, e.matches, e.copies
+        )
+        .unwrap();
+    }
+    for e in explanations {
+        for (source, copies) in &e.sources {
+            writeln!(
+                out,
+                "    card{} -> card{} [label=\"{}\"];",
+                source, e.id, copies
+            )
+            .unwrap();
+        }
+    }
+    out.push_str("}\n");
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scratchcard::parse_cards;
+
+    fn example() -> Vec<Scratchcard> {
+        let lines = vec![
+            String::from("Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53"),
+            String::from("Card 2: 13 32 20 16 61 | 61 30 68 82 17 32 24 19"),
+            String::from("Card 3:  1 21 53 59 44 | 69 82 63 72 16 21 14  1"),
+            String::from("Card 4: 41 92 73 84 69 | 59 84 76 51 58  5 54 83"),
+            String::from("Card 5: 87 83 26 28 32 | 88 30 70 12 93 22 82 36"),
+            String::from("Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11"),
+        ];
+        parse_cards(&lines).unwrap()
+    }
+
+    #[test]
+    fn explain_test() {
+        let explanations = explain(&example(), PastEndPolicy::Error).unwrap();
+
+        let copies: Vec<u128> = explanations.iter().map(|e| e.copies).collect();
+        assert_eq!(copies, vec![1, 2, 4, 8, 14, 1]);
+        assert_eq!(explanations[4].sources, vec![(1, 1), (3, 4), (4, 8)]);
+
+        // Every card's copies are its original plus what its sources sent it.
+        for e in &explanations {
+            let sent: u128 = e.sources.iter().map(|(_, copies)| copies).sum();
+            assert_eq!(e.copies, 1 + sent);
+        }
+
+        let table = format_table(&explanations).unwrap();
+        assert!(table
+            .contains("Card 5: 1 original + 1 from card 1 + 4 from card 3 + 8 from card 4 = 14"));
+    }
+
+    #[test]
+    fn table_overflow_test() {
+        let explanation = |id, points, copies| CardExplanation {
+            id,
+            matches: 0,
+            points,
+            copies,
+            sources: Vec::new(),
+        };
+
+        let copies = [explanation(1, 0, u128::MAX), explanation(2, 0, 1)];
+        assert_eq!(
+            format_table(&copies),
+            Err(CascadeError::Overflow { card: 2 })
+        );
+        let points = [explanation(1, 1 << 127, 1), explanation(2, 1 << 127, 1)];
+        assert_eq!(
+            format_table(&points),
+            Err(CascadeError::Overflow { card: 2 })
+        );
+    }
+
+    #[test]
+    fn dot_test() {
+        let explanations = explain(&example(), PastEndPolicy::Error).unwrap();
+        let dot = format_dot(&explanations);
+
+        assert!(dot.starts_with("digraph cascade {"));
+        assert!(dot.contains("card4 -> card5 [label=\"8\"];"));
+        assert!(!dot.contains("-> card1 "));
+        assert_eq!(dot.matches("->").count(), 4 + 2 + 2 + 1);
+    }
+}
@@ -1,72 +1,81 @@
-use regex::Regex;
-use std::collections::HashSet;
+mod cascade;
+mod explain;
+mod numberset;
+mod scratchcard;
+mod stream;
+
+use cascade::{CascadeError, PastEndPolicy};
+use scratchcard::Scratchcard;
 use std::fs::File;
 use std::io::{BufRead, BufReader};
 
-fn part1(lines: &Vec<String>) -> u32 {
-    let mut result = 0;
-
-    let re =
-        Regex::new(r"Card +(?<cardnum>\d+): +(?<winningnums>\d.*) \| +(?<guesses>\d.*)$").unwrap();
-    for line in lines {
-        let captures = re.captures(line).unwrap();
-        let winning_nums: HashSet<u32> = captures["winningnums"]
-            .split_whitespace()
-            .map(|n| n.parse::<u32>().unwrap())
-            .collect();
-        let guesses: Vec<u32> = captures["guesses"]
-            .split_whitespace()
-            .map(|n| n.parse::<u32>().unwrap())
-            .collect();
-
-        let matching = guesses.iter().filter(|g| winning_nums.contains(&g)).count() as u32;
-        if matching > 0 {
-            result += u32::pow(2, matching - 1);
-        }
-    }
-
-    return result;
+fn part1(cards: &[Scratchcard]) -> Result<u128, CascadeError> {
+    cards.iter().try_fold(0u128, |total, card| {
+        card.points()
+            .and_then(|points| total.checked_add(points))
+            .ok_or(CascadeError::Overflow { card: card.id })
+    })
 }
 
-fn part2(lines: &Vec<String>) -> u32 {
-    let re =
-        Regex::new(r"Card +(?<cardnum>\d+): +(?<winningnums>\d.*) \| +(?<guesses>\d.*)$").unwrap();
-
-    let card_match_counts: Vec<u32> = lines
-        .iter()
-        .map(|line| {
-            let captures = re.captures(line).unwrap();
-            let winning_nums: HashSet<u32> = captures["winningnums"]
-                .split_whitespace()
-                .map(|n| n.parse::<u32>().unwrap())
-                .collect();
-            let guesses: Vec<u32> = captures["guesses"]
-                .split_whitespace()
-                .map(|n| n.parse::<u32>().unwrap())
-                .collect();
-
-            let matching = guesses.iter().filter(|g| winning_nums.contains(&g)).count() as u32;
-            matching
-        })
-        .collect();
-
-    let mut card_counts = vec![1 as u32; card_match_counts.len()];
-    for i in 0..card_match_counts.len() {
-        for j in 1..=card_match_counts[i] {
-            card_counts[i + j as usize] += card_counts[i];
-        }
-    }
+fn part2(cards: &[Scratchcard], policy: PastEndPolicy) -> Result<u128, CascadeError> {
+    cascade::total_cards(cards, policy)
+}
 
-    return card_counts.iter().sum();
+fn exit_with(err: impl std::fmt::Display) -> ! {
+    eprintln!("{}", err);
+    std::process::exit(1);
 }
 
 fn main() {
+    // The puzzle promises no card wins copies past the end of the table, so by default treat that
+    // as bad input. "--past-end clamp" ignores the missing cards instead.
+    let mut policy = PastEndPolicy::Error;
+    // "explain" prints how the part 2 total arises, "explain --dot" the cascade as a graph.
+    let mut explain = false;
+    let mut dot = false;
+    // "--stream" solves both parts in one pass without reading the whole input into memory.
+    let mut streaming = false;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--past-end" => {
+                let value = args.next().unwrap_or_default();
+                policy = value.parse().unwrap_or_else(|err| exit_with(err));
+            }
+            "explain" => explain = true,
+            "--dot" => dot = true,
+            "--stream" => streaming = true,
+            _ => exit_with(format!("unknown argument '{}'", arg)),
+        }
+    }
+
     let file = File::open("input").unwrap();
     let reader = BufReader::new(file);
+
+    if streaming {
+        let (pt1_result, pt2_result) =
+            stream::solve(reader, policy).unwrap_or_else(|err| exit_with(err));
+        println!("Part 1: {}, Part 2: {}", pt1_result, pt2_result);
+        return;
+    }
+
     let lines: Vec<String> = reader.lines().map(|l| l.unwrap()).collect();
 
-    let pt1_result = part1(&lines);
-    let pt2_result = part2(&lines);
+    let cards = scratchcard::parse_cards(&lines).unwrap_or_else(|err| exit_with(err));
+
+    if explain {
+        let explanations = explain::explain(&cards, policy).unwrap_or_else(|err| exit_with(err));
+        if dot {
+            print!("{}", explain::format_dot(&explanations));
+        } else {
+            let table = explain::format_table(&explanations).unwrap_or_else(|err| exit_with(err));
+            print!("{}", table);
+        }
+        return;
+    }
+
+    let pt1_result = part1(&cards).unwrap_or_else(|err| exit_with(err));
+    let pt2_result = part2(&cards, policy).unwrap_or_else(|err| exit_with(err));
     println!("Part 1: {}, Part 2: {}", pt1_result, pt2_result);
 }
 
@@ -85,8 +94,30 @@ mod tests {
             String::from("Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11"),
         ];
 
-        let result = part1(&lines);
-        assert_eq!(result, 13);
+        let result = part1(&scratchcard::parse_cards(&lines).unwrap());
+        assert_eq!(result, Ok(13));
+    }
+
+    #[test]
+    fn pt1_overflow_test() {
+        // A card with n matches is worth 2^(n-1) points.
+        let card = |id: u32, matches: u32| {
+            let numbers: Vec<String> = (0..matches).map(|n| n.to_string()).collect();
+            let numbers = numbers.join(" ");
+            format!("Card {}: {} | {}", id, numbers, numbers)
+        };
+
+        let lines = vec![card(1, 128), card(2, 127)];
+        let cards = scratchcard::parse_cards(&lines).unwrap();
+        assert_eq!(part1(&cards), Ok(3 << 126));
+
+        let lines = vec![card(1, 128), card(2, 128)];
+        let cards = scratchcard::parse_cards(&lines).unwrap();
+        assert_eq!(part1(&cards), Err(CascadeError::Overflow { card: 2 }));
+
+        let lines = vec![card(1, 129)];
+        let cards = scratchcard::parse_cards(&lines).unwrap();
+        assert_eq!(part1(&cards), Err(CascadeError::Overflow { card: 1 }));
     }
 
     #[test]
@@ -100,7 +131,9 @@ mod tests {
             String::from("Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11"),
         ];
 
-        let result = part2(&lines);
+        let cards = scratchcard::parse_cards(&lines).unwrap();
+        assert_eq!(part2(&cards, PastEndPolicy::Error), Ok(30));
+        let result = part2(&cards, PastEndPolicy::Clamp).unwrap();
         assert_eq!(result, 30);
     }
 }
@@ -0,0 +1,98 @@
+// A set of numbers. Small numbers, which is all the puzzle uses, are stored as a fixed-width
+// bitmask so intersecting two cards' numbers is a single AND and counting the matches is a
+// popcount. Anything larger spills into a sorted list.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NumberSet {
+    mask: u128,
+    large: Vec<u32>,
+}
+
+impl NumberSet {
+    // Largest number stored in the mask.
+    pub const MASK_MAX: u32 = u128::BITS - 1;
+
+    // Adds n to the set, returning false if it was already present.
+    pub fn insert(&mut self, n: u32) -> bool {
+        if n <= Self::MASK_MAX {
+            let bit = 1u128 << n;
+            let inserted = self.mask & bit == 0;
+            self.mask |= bit;
+            inserted
+        } else {
+            match self.large.binary_search(&n) {
+                Ok(_) => false,
+                Err(i) => {
+                    self.large.insert(i, n);
+                    true
+                }
+            }
+        }
+    }
+
+    pub fn count(&self) -> u32 {
+        self.mask.count_ones() + self.large.len() as u32
+    }
+
+    pub fn intersection(&self, other: &NumberSet) -> NumberSet {
+        NumberSet {
+            mask: self.mask & other.mask,
+            large: self
+                .large
+                .iter()
+                .copied()
+                .filter(|n| other.large.binary_search(n).is_ok())
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn number_set_test() {
+        let mut winning = NumberSet::default();
+        for n in [41, 48, 83, 86, 17] {
+            assert!(winning.insert(n));
+        }
+        assert!(!winning.insert(48));
+        assert_eq!(winning.count(), 5);
+
+        let mut guesses = NumberSet::default();
+        for n in [83, 86, 6, 31, 17, 9, 48, 53, 0, NumberSet::MASK_MAX] {
+            guesses.insert(n);
+        }
+        assert_eq!(guesses.count(), 10);
+
+        let mut expected = NumberSet::default();
+        for n in [17, 48, 83, 86] {
+            expected.insert(n);
+        }
+        assert_eq!(winning.intersection(&guesses), expected);
+        assert_eq!(winning.intersection(&guesses).count(), 4);
+        assert_eq!(NumberSet::default().intersection(&guesses).count(), 0);
+    }
+
+    #[test]
+    fn large_numbers_test() {
+        let mut winning = NumberSet::default();
+        for n in [5, 128, u32::MAX, 1000] {
+            assert!(winning.insert(n));
+        }
+        assert!(!winning.insert(1000));
+        assert_eq!(winning.count(), 4);
+
+        let mut guesses = NumberSet::default();
+        for n in [1000, 5, 129, u32::MAX] {
+            guesses.insert(n);
+        }
+
+        let mut expected = NumberSet::default();
+        for n in [u32::MAX, 5, 1000] {
+            expected.insert(n);
+        }
+        assert_eq!(winning.intersection(&guesses), expected);
+        assert_eq!(winning.intersection(&guesses).count(), 3);
+    }
+}
@@ -0,0 +1,219 @@
+use crate::numberset::NumberSet;
+use regex::Regex;
+use std::fmt;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ParseError {
+    Malformed {
+        line: usize,
+    },
+    BadNumber {
+        line: usize,
+        value: String,
+    },
+    NonSequentialId {
+        line: usize,
+        expected: u32,
+        found: u32,
+    },
+    DuplicateNumber {
+        line: usize,
+        card: u32,
+        number: u32,
+    },
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::Malformed { line } => write!(f, "line {}: not a scratchcard", line),
+            ParseError::BadNumber { line, value } => {
+                write!(f, "line {}: '{}' is not a valid number", line, value)
+            }
+            ParseError::NonSequentialId {
+                line,
+                expected,
+                found,
+            } => write!(
+                f,
+                "line {}: expected card {} but found card {}",
+                line, expected, found
+            ),
+            ParseError::DuplicateNumber { line, card, number } => write!(
+                f,
+                "line {}: card {} has number {} more than once",
+                line, card, number
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Scratchcard {
+    pub id: u32,
+    pub winning: NumberSet,
+    pub guesses: NumberSet,
+    matches: u32,
+}
+
+impl Scratchcard {
+    pub fn new(id: u32, winning: NumberSet, guesses: NumberSet) -> Scratchcard {
+        let matches = winning.intersection(&guesses).count();
+        Scratchcard {
+            id,
+            winning,
+            guesses,
+            matches,
+        }
+    }
+
+    // Number of guesses that appear in the winning numbers, computed once on construction.
+    pub fn matches(&self) -> u32 {
+        self.matches
+    }
+
+    // None if the points don't fit in a u128, which takes more than 128 matches.
+    pub fn points(&self) -> Option<u128> {
+        if self.matches > 0 {
+            2u128.checked_pow(self.matches - 1)
+        } else {
+            Some(0)
+        }
+    }
+}
+
+fn parse_numbers(line: usize, card: u32, s: &str) -> Result<NumberSet, ParseError> {
+    let mut set = NumberSet::default();
+    for n in s.split_whitespace() {
+        let number = n.parse::<u32>().map_err(|_| ParseError::BadNumber {
+            line,
+            value: n.to_string(),
+        })?;
+        if !set.insert(number) {
+            return Err(ParseError::DuplicateNumber { line, card, number });
+        }
+    }
+
+    Ok(set)
+}
+
+// Parses cards one line at a time. Card ids must start at 1 and increase by one per line, since
+// part 2 relies on a card's position to find the cards it wins copies of.
+pub struct CardParser {
+    re: Regex,
+    line: usize,
+}
+
+impl CardParser {
+    pub fn new() -> CardParser {
+        CardParser {
+            re: Regex::new(
+                r"^Card +(?<cardnum>\d+): +(?<winningnums>[\d ]*)\| +(?<guesses>[\d ]*)$",
+            )
+            .unwrap(),
+            line: 0,
+        }
+    }
+
+    pub fn parse_line(&mut self, line: &str) -> Result<Scratchcard, ParseError> {
+        self.line += 1;
+        let line_num = self.line;
+        let captures = self
+            .re
+            .captures(line.trim_end())
+            .ok_or(ParseError::Malformed { line: line_num })?;
+
+        let id = captures["cardnum"]
+            .parse::<u32>()
+            .map_err(|_| ParseError::BadNumber {
+                line: line_num,
+                value: captures["cardnum"].to_string(),
+            })?;
+        let expected = line_num as u32;
+        if id != expected {
+            return Err(ParseError::NonSequentialId {
+                line: line_num,
+                expected,
+                found: id,
+            });
+        }
+
+        let winning = parse_numbers(line_num, id, &captures["winningnums"])?;
+        let guesses = parse_numbers(line_num, id, &captures["guesses"])?;
+        Ok(Scratchcard::new(id, winning, guesses))
+    }
+}
+
+impl Default for CardParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub fn parse_cards(lines: &[String]) -> Result<Vec<Scratchcard>, ParseError> {
+    let mut parser = CardParser::new();
+    lines.iter().map(|line| parser.parse_line(line)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_test() {
+        let lines = vec![
+            String::from("Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53"),
+            String::from("Card 2: 13 32 20 16 61 | 61 30 68 82 17 32 24 19"),
+        ];
+        let cards = parse_cards(&lines).unwrap();
+
+        assert_eq!(cards[0].id, 1);
+        let mut winning = NumberSet::default();
+        for n in [41, 48, 83, 86, 17] {
+            winning.insert(n);
+        }
+        assert_eq!(cards[0].winning, winning);
+        assert_eq!(cards[0].matches(), 4);
+        assert_eq!(cards[0].points(), Some(8));
+        assert_eq!(cards[1].matches(), 2);
+    }
+
+    #[test]
+    fn parse_errors_test() {
+        let skipped = vec![
+            String::from("Card 1: 1 2 | 3 4"),
+            String::from("Card 3: 1 2 | 3 4"),
+        ];
+        assert_eq!(
+            parse_cards(&skipped),
+            Err(ParseError::NonSequentialId {
+                line: 2,
+                expected: 2,
+                found: 3
+            })
+        );
+
+        let duplicate = vec![
+            String::from("Card 1: 1 2 | 3 4"),
+            String::from("Card 2: 1 2 | 3 4 3"),
+        ];
+        assert_eq!(
+            parse_cards(&duplicate),
+            Err(ParseError::DuplicateNumber {
+                line: 2,
+                card: 2,
+                number: 3
+            })
+        );
+
+        // Numbers past the bitmask still parse and match.
+        let large = vec![String::from("Card 1: 1 200 4000000000 | 200 4000000000 5")];
+        assert_eq!(parse_cards(&large).unwrap()[0].matches(), 2);
+
+        let malformed = vec![String::from("Card 1: 1 2 3 4")];
+        assert_eq!(
+            parse_cards(&malformed),
+            Err(ParseError::Malformed { line: 1 })
+        );
+    }
+}
@@ -0,0 +1,190 @@
+use crate::cascade::{CascadeError, PastEndPolicy};
+use crate::scratchcard::{CardParser, ParseError};
+use std::collections::VecDeque;
+use std::fmt;
+use std::io::BufRead;
+
+#[derive(Debug)]
+pub enum StreamError {
+    Io(std::io::Error),
+    Parse(ParseError),
+    Cascade(CascadeError),
+}
+
+impl fmt::Display for StreamError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            StreamError::Io(err) => write!(f, "{}", err),
+            StreamError::Parse(err) => write!(f, "{}", err),
+            StreamError::Cascade(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl From<std::io::Error> for StreamError {
+    fn from(err: std::io::Error) -> Self {
+        StreamError::Io(err)
+    }
+}
+
+impl From<ParseError> for StreamError {
+    fn from(err: ParseError) -> Self {
+        StreamError::Parse(err)
+    }
+}
+
+impl From<CascadeError> for StreamError {
+    fn from(err: CascadeError) -> Self {
+        StreamError::Cascade(err)
+    }
+}
+
+// Computes both parts in a single pass over the input, one line at a time. Only the copies won for
+// the next few cards are kept, so memory is bounded by the largest match count rather than the
+// number of cards.
+pub fn solve<R: BufRead>(reader: R, policy: PastEndPolicy) -> Result<(u128, u128), StreamError> {
+    let mut parser = CardParser::new();
+    let mut points: u128 = 0;
+    let mut total: u128 = 0;
+
+    // pending[k] is the number of copies won so far of the card k places after the current one.
+    let mut pending: VecDeque<u128> = VecDeque::new();
+
+    // Cards whose wins reach further than any earlier card, as (id, matches, furthest card won).
+    // Once the last card is known, the first of these reaching past it is the card the batch
+    // cascade would have reported. Entries that can no longer reach past the end are dropped, so
+    // this stays within the match window too.
+    let mut furthest: VecDeque<(u32, u32, u64)> = VecDeque::new();
+
+    let mut last_id = 0;
+    for line in reader.lines() {
+        let card = parser.parse_line(&line?)?;
+        last_id = card.id;
+
+        let count = 1 + pending.pop_front().unwrap_or(0);
+        points = card
+            .points()
+            .and_then(|p| points.checked_add(p))
+            .ok_or(CascadeError::Overflow { card: card.id })?;
+        total = total
+            .checked_add(count)
+            .ok_or(CascadeError::Overflow { card: card.id })?;
+
+        let matches = card.matches() as usize;
+        if pending.len() < matches {
+            pending.resize(matches, 0);
+        }
+        for copies in pending.iter_mut().take(matches) {
+            *copies = copies
+                .checked_add(count)
+                .ok_or(CascadeError::Overflow { card: card.id })?;
+        }
+
+        let reach = card.id as u64 + card.matches() as u64;
+        while furthest
+            .front()
+            .is_some_and(|&(_, _, r)| r <= card.id as u64)
+        {
+            furthest.pop_front();
+        }
+        if furthest.back().is_none_or(|&(_, _, r)| reach > r) {
+            furthest.push_back((card.id, card.matches(), reach));
+        }
+    }
+
+    if policy == PastEndPolicy::Error {
+        if let Some(&(card, matches, _)) = furthest.iter().find(|&&(_, _, r)| r > last_id as u64) {
+            return Err(CascadeError::PastLastCard {
+                card,
+                matches,
+                last: last_id,
+            }
+            .into());
+        }
+    }
+
+    Ok((points, total))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cascade;
+    use crate::scratchcard::parse_cards;
+
+    // A xorshift generator, seeded the same every time so the test is repeatable.
+    struct Rng(u64);
+
+    impl Rng {
+        fn new() -> Rng {
+            Rng(0x2545f4914f6cdd1d)
+        }
+
+        // A value in 0..bound.
+        fn below(&mut self, bound: usize) -> usize {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            (self.0 % bound as u64) as usize
+        }
+    }
+
+    // Generates n sequential cards with pseudo-random numbers in 1..=99.
+    fn generate(n: u32) -> Vec<String> {
+        let mut rng = Rng::new();
+        let mut next = || rng.below(99) as u32 + 1;
+
+        (1..=n)
+            .map(|id| {
+                let mut pick = |count: usize| {
+                    let mut nums: Vec<u32> = Vec::new();
+                    while nums.len() < count {
+                        let n = next();
+                        if !nums.contains(&n) {
+                            nums.push(n);
+                        }
+                    }
+                    nums.iter()
+                        .map(|n| format!("{:>2}", n))
+                        .collect::<Vec<String>>()
+                        .join(" ")
+                };
+                let winning = pick(10);
+                let guesses = pick(10);
+                format!("Card {}: {} | {}", id, winning, guesses)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn stream_matches_batch_test() {
+        let lines = generate(2000);
+        let cards = parse_cards(&lines).unwrap();
+        let expected_points: u128 = cards.iter().map(|c| c.points().unwrap()).sum();
+        let expected_total = cascade::total_cards(&cards, PastEndPolicy::Clamp).unwrap();
+
+        let input = lines.join("\n");
+        let (points, total) = solve(input.as_bytes(), PastEndPolicy::Clamp).unwrap();
+        assert_eq!(points, expected_points);
+        assert_eq!(total, expected_total);
+    }
+
+    #[test]
+    fn stream_past_end_test() {
+        let input = "Card 1: 1 2 3 | 1 2 3\nCard 2: 1 2 | 1 2\nCard 3: 1 | 2";
+        let cards = parse_cards(&input.lines().map(String::from).collect::<Vec<String>>()).unwrap();
+
+        assert_eq!(
+            solve(input.as_bytes(), PastEndPolicy::Clamp).unwrap().1,
+            cascade::total_cards(&cards, PastEndPolicy::Clamp).unwrap()
+        );
+
+        // Card 1 reaches card 4 and card 2 reaches card 4; the batch cascade reports card 1.
+        match solve(input.as_bytes(), PastEndPolicy::Error) {
+            Err(StreamError::Cascade(err)) => {
+                assert_eq!(Err(err), cascade::card_counts(&cards, PastEndPolicy::Error))
+            }
+            other => panic!("expected a cascade error, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,461 @@
+use crate::rangemap::RangeMap;
+use regex::Regex;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt;
+use std::ops::Range;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GardenMapEntry {
+    pub src_start: i64,
+    pub dst_start: i64,
+    pub count: i64,
+    // Line number in the input, for reporting.
+    pub line: usize,
+}
+
+// One "<source>-to-<destination> map:" section.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AlmanacMap {
+    pub source: String,
+    pub destination: String,
+    pub entries: Vec<GardenMapEntry>,
+    // Line number of the map's header.
+    pub line: usize,
+}
+
+impl AlmanacMap {
+    // Fails on entries whose source or destination range runs past i64::MAX, which the parser
+    // accepts so that lint can report them.
+    pub fn range_map(&self) -> Result<RangeMap, AlmanacError> {
+        for entry in &self.entries {
+            for (field, start) in [
+                ("source", entry.src_start),
+                ("destination", entry.dst_start),
+            ] {
+                if start.checked_add(entry.count).is_none() {
+                    return Err(AlmanacError::EntryOverflow {
+                        line: entry.line,
+                        field,
+                    });
+                }
+            }
+        }
+
+        Ok(RangeMap::from_entries(
+            self.entries
+                .iter()
+                .map(|e| (e.src_start, e.dst_start, e.count)),
+        ))
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum AlmanacError {
+    Malformed {
+        line: usize,
+        text: String,
+    },
+    EntryOutsideMap {
+        line: usize,
+    },
+    EntryOverflow {
+        line: usize,
+        field: &'static str,
+    },
+    MissingSeeds,
+    NoSeeds {
+        line: usize,
+    },
+    OddSeedCount {
+        line: usize,
+        count: usize,
+    },
+    BadSeedRange {
+        line: usize,
+        start: i64,
+        length: i64,
+    },
+    DuplicateMap {
+        source: String,
+        destination: String,
+    },
+    UnknownCategory(String),
+    NoPath {
+        from: String,
+        to: String,
+        reachable: Vec<String>,
+    },
+}
+
+impl fmt::Display for AlmanacError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AlmanacError::Malformed { line, text } => {
+                write!(f, "line {}: could not parse '{}'", line, text)
+            }
+            AlmanacError::EntryOutsideMap { line } => {
+                write!(f, "line {}: map entry appears before any map header", line)
+            }
+            AlmanacError::EntryOverflow { line, field } => write!(
+                f,
+                "line {}: {} + count overflows a 64-bit integer",
+                line, field
+            ),
+            AlmanacError::MissingSeeds => write!(f, "no 'seeds:' line"),
+            AlmanacError::NoSeeds { line } => write!(f, "line {}: no seeds listed", line),
+            AlmanacError::OddSeedCount { line, count } => write!(
+                f,
+                "line {}: {} seed numbers can't be read as (start, length) pairs",
+                line, count
+            ),
+            AlmanacError::BadSeedRange {
+                line,
+                start,
+                length,
+            } => write!(
+                f,
+                "line {}: seed range {} with length {} is empty or overflows a 64-bit integer",
+                line, start, length
+            ),
+            AlmanacError::DuplicateMap {
+                source,
+                destination,
+            } => write!(f, "more than one {}-to-{} map", source, destination),
+            AlmanacError::UnknownCategory(category) => {
+                write!(f, "no map converts to or from '{}'", category)
+            }
+            AlmanacError::NoPath {
+                from,
+                to,
+                reachable,
+            } => write!(
+                f,
+                "no chain of maps converts {} to {}; from {} only {} can be reached",
+                from,
+                to,
+                from,
+                if reachable.is_empty() {
+                    String::from("nothing")
+                } else {
+                    reachable.join(", ")
+                }
+            ),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Almanac {
+    pub seeds: Vec<i64>,
+    pub maps: Vec<AlmanacMap>,
+}
+
+impl Almanac {
+    // Parses the seeds line and every map section. Sections may appear in any order; how they
+    // link up is only checked when a conversion is requested.
+    pub fn parse(lines: &[String]) -> Result<Almanac, AlmanacError> {
+        let seeds_regex = Regex::new(r"^seeds:(?<seeds>[\d ]*)$").unwrap();
+        let map_delim_regex = Regex::new(r"^(?<src>[a-z]+)-to-(?<dst>[a-z]+) map:$").unwrap();
+        let map_entry_regex =
+            Regex::new(r"^(?<dst_start>\d+) +(?<src_start>\d+) +(?<count>\d+)$").unwrap();
+
+        let mut seeds: Vec<i64> = vec![];
+        let mut seeds_line: Option<usize> = None;
+        let mut maps: Vec<AlmanacMap> = vec![];
+
+        for (i, line) in lines.iter().enumerate() {
+            let line_num = i + 1;
+            let line = line.trim();
+            let malformed = || AlmanacError::Malformed {
+                line: line_num,
+                text: line.to_string(),
+            };
+
+            if line.is_empty() {
+                continue;
+            } else if let Some(caps) = seeds_regex.captures(line) {
+                seeds = caps["seeds"]
+                    .split_whitespace()
+                    .map(|s| s.parse().map_err(|_| malformed()))
+                    .collect::<Result<_, _>>()?;
+                seeds_line = Some(line_num);
+            } else if let Some(caps) = map_delim_regex.captures(line) {
+                let (source, destination) = (&caps["src"], &caps["dst"]);
+                if maps
+                    .iter()
+                    .any(|m| m.source == source && m.destination == destination)
+                {
+                    return Err(AlmanacError::DuplicateMap {
+                        source: source.to_string(),
+                        destination: destination.to_string(),
+                    });
+                }
+                maps.push(AlmanacMap {
+                    source: source.to_string(),
+                    destination: destination.to_string(),
+                    entries: vec![],
+                    line: line_num,
+                });
+            } else if let Some(caps) = map_entry_regex.captures(line) {
+                let cur_map = maps
+                    .last_mut()
+                    .ok_or(AlmanacError::EntryOutsideMap { line: line_num })?;
+                cur_map.entries.push(GardenMapEntry {
+                    src_start: caps["src_start"].parse().map_err(|_| malformed())?,
+                    dst_start: caps["dst_start"].parse().map_err(|_| malformed())?,
+                    count: caps["count"].parse().map_err(|_| malformed())?,
+                    line: line_num,
+                });
+            } else {
+                return Err(malformed());
+            }
+        }
+
+        Self::check_seeds(&seeds, seeds_line)?;
+        Ok(Almanac { seeds, maps })
+    }
+
+    // Both parts need at least one seed, and part 2 reads them as non-empty (start, count)
+    // pairs, so seed_ranges can't fail once parsing has succeeded.
+    fn check_seeds(seeds: &[i64], seeds_line: Option<usize>) -> Result<(), AlmanacError> {
+        let line = seeds_line.ok_or(AlmanacError::MissingSeeds)?;
+        if seeds.is_empty() {
+            return Err(AlmanacError::NoSeeds { line });
+        }
+        if !seeds.len().is_multiple_of(2) {
+            return Err(AlmanacError::OddSeedCount {
+                line,
+                count: seeds.len(),
+            });
+        }
+        for pair in seeds.chunks(2) {
+            let (start, length) = (pair[0], pair[1]);
+            if length == 0 || start.checked_add(length).is_none() {
+                return Err(AlmanacError::BadSeedRange {
+                    line,
+                    start,
+                    length,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    // Part 2 reads the seeds line as (start, count) pairs.
+    pub fn seed_ranges(&self) -> Vec<Range<i64>> {
+        self.seeds.chunks(2).map(|s| s[0]..(s[0] + s[1])).collect()
+    }
+
+    pub fn categories(&self) -> HashSet<&str> {
+        self.maps
+            .iter()
+            .flat_map(|m| [m.source.as_str(), m.destination.as_str()])
+            .collect()
+    }
+
+    // The shortest chain of maps converting from one category to another, as indices into maps.
+    pub fn path(&self, from: &str, to: &str) -> Result<Vec<usize>, AlmanacError> {
+        let categories = self.categories();
+        for category in [from, to] {
+            if !categories.contains(category) {
+                return Err(AlmanacError::UnknownCategory(category.to_string()));
+            }
+        }
+
+        // Breadth-first search, remembering the map used to first reach each category.
+        let mut reached_by: HashMap<&str, usize> = HashMap::new();
+        let mut queue: VecDeque<&str> = VecDeque::from([from]);
+        while let Some(category) = queue.pop_front() {
+            if category == to {
+                let mut path = vec![];
+                let mut cur = to;
+                while cur != from {
+                    let map = reached_by[cur];
+                    path.push(map);
+                    cur = &self.maps[map].source;
+                }
+                path.reverse();
+                return Ok(path);
+            }
+
+            for (i, map) in self.maps.iter().enumerate() {
+                let next = map.destination.as_str();
+                if map.source == category && next != from && !reached_by.contains_key(next) {
+                    reached_by.insert(next, i);
+                    queue.push_back(next);
+                }
+            }
+        }
+
+        let mut reachable: Vec<String> = reached_by.keys().map(|c| c.to_string()).collect();
+        reachable.sort();
+        Err(AlmanacError::NoPath {
+            from: from.to_string(),
+            to: to.to_string(),
+            reachable,
+        })
+    }
+
+    // A single map performing the whole conversion from one category to another.
+    pub fn conversion(&self, from: &str, to: &str) -> Result<RangeMap, AlmanacError> {
+        self.path(from, to)?
+            .iter()
+            .try_fold(RangeMap::identity(), |acc, &i| {
+                Ok(acc.compose(&self.maps[i].range_map()?))
+            })
+    }
+
+    // Every seed value, listed or not, whose location falls in the given range.
+    pub fn seeds_for_locations(
+        &self,
+        locations: &Range<i64>,
+    ) -> Result<Vec<Range<i64>>, AlmanacError> {
+        Ok(self.seed_to_location()?.preimage(locations))
+    }
+
+    pub fn seed_to_location(&self) -> Result<RangeMap, AlmanacError> {
+        self.conversion("seed", "location")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(text: &str) -> Vec<String> {
+        text.lines().map(String::from).collect()
+    }
+
+    #[test]
+    fn out_of_order_sections_test() {
+        let almanac = Almanac::parse(&lines(
+            "seeds: 1 2
+
+soil-to-water map:
+10 0 5
+
+seed-to-soil map:
+0 5 5
+
+water-to-location map:
+100 10 5",
+        ))
+        .unwrap();
+
+        let names: Vec<&str> = almanac
+            .path("seed", "location")
+            .unwrap()
+            .iter()
+            .map(|&i| almanac.maps[i].source.as_str())
+            .collect();
+        assert_eq!(names, vec!["seed", "soil", "water"]);
+
+        // 7 -> soil 2 -> water 12 -> location 102
+        assert_eq!(almanac.seed_to_location().unwrap().apply(7), 102);
+        assert_eq!(almanac.conversion("soil", "water").unwrap().apply(2), 12);
+        assert_eq!(
+            almanac.conversion("soil", "soil").unwrap(),
+            RangeMap::identity()
+        );
+    }
+
+    #[test]
+    fn conversion_errors_test() {
+        let almanac = Almanac::parse(&lines(
+            "seeds: 1 2
+seed-to-soil map:
+0 5 5
+water-to-location map:
+100 10 5",
+        ))
+        .unwrap();
+
+        assert_eq!(
+            almanac.seed_to_location().unwrap_err(),
+            AlmanacError::NoPath {
+                from: String::from("seed"),
+                to: String::from("location"),
+                reachable: vec![String::from("soil")],
+            }
+        );
+        assert_eq!(
+            almanac.conversion("seed", "humidity").unwrap_err(),
+            AlmanacError::UnknownCategory(String::from("humidity"))
+        );
+    }
+
+    #[test]
+    fn overflowing_entry_test() {
+        // Parses, so lint can see it, but can't be converted.
+        let almanac = Almanac::parse(&lines(
+            "seeds: 1 2
+seed-to-soil map:
+9223372036854775800 0 100
+soil-to-location map:
+1 0 5",
+        ))
+        .unwrap();
+        assert_eq!(
+            almanac.seed_to_location().unwrap_err(),
+            AlmanacError::EntryOverflow {
+                line: 3,
+                field: "destination"
+            }
+        );
+        assert!(almanac.conversion("soil", "location").is_ok());
+    }
+
+    #[test]
+    fn parse_errors_test() {
+        assert_eq!(
+            Almanac::parse(&lines("seeds: 1 2\n0 5 5")).unwrap_err(),
+            AlmanacError::EntryOutsideMap { line: 2 }
+        );
+        assert_eq!(
+            Almanac::parse(&lines("seed-to-soil map:\n0 5\n")).unwrap_err(),
+            AlmanacError::Malformed {
+                line: 2,
+                text: String::from("0 5")
+            }
+        );
+        assert_eq!(
+            Almanac::parse(&lines("seed-to-soil map:\nseed-to-soil map:")).unwrap_err(),
+            AlmanacError::DuplicateMap {
+                source: String::from("seed"),
+                destination: String::from("soil")
+            }
+        );
+    }
+
+    #[test]
+    fn seed_errors_test() {
+        let map = "\nseed-to-location map:\n0 5 5";
+        let parse = |seeds: &str| Almanac::parse(&lines(&format!("{}{}", seeds, map)));
+        assert_eq!(parse("").unwrap_err(), AlmanacError::MissingSeeds);
+        assert_eq!(
+            parse("seeds:").unwrap_err(),
+            AlmanacError::NoSeeds { line: 1 }
+        );
+        assert_eq!(
+            parse("seeds: 1 2 3").unwrap_err(),
+            AlmanacError::OddSeedCount { line: 1, count: 3 }
+        );
+        assert_eq!(
+            parse("seeds: 1 2 7 0").unwrap_err(),
+            AlmanacError::BadSeedRange {
+                line: 1,
+                start: 7,
+                length: 0
+            }
+        );
+        assert_eq!(
+            parse("seeds: 9223372036854775800 100").unwrap_err(),
+            AlmanacError::BadSeedRange {
+                line: 1,
+                start: 9223372036854775800,
+                length: 100
+            }
+        );
+        assert_eq!(parse("seeds: 1 2").unwrap().seed_ranges(), vec![1..3]);
+    }
+}
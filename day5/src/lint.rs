@@ -0,0 +1,266 @@
+use crate::almanac::{Almanac, AlmanacMap, GardenMapEntry};
+use std::fmt;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    // Legal input that is worth knowing about.
+    Note,
+    // Input that is almost certainly a mistake, or that the solver can't handle.
+    Warning,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum FindingKind {
+    EmptyRange,
+    Duplicate {
+        of_line: usize,
+    },
+    Overlap {
+        with_line: usize,
+        start: i64,
+        end: i64,
+    },
+    Overflow {
+        field: &'static str,
+    },
+    IdentityGap {
+        start: i64,
+        end: i64,
+    },
+    EmptyMap,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Finding {
+    pub line: usize,
+    pub kind: FindingKind,
+}
+
+impl Finding {
+    pub fn severity(&self) -> Severity {
+        match self.kind {
+            FindingKind::IdentityGap { .. } => Severity::Note,
+            _ => Severity::Warning,
+        }
+    }
+}
+
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let severity = match self.severity() {
+            Severity::Note => "note",
+            Severity::Warning => "warning",
+        };
+        write!(f, "line {}: {}: ", self.line, severity)?;
+
+        match &self.kind {
+            FindingKind::EmptyRange => write!(f, "entry has a count of zero and maps nothing"),
+            FindingKind::Duplicate { of_line } => {
+                write!(f, "entry duplicates line {}", of_line)
+            }
+            FindingKind::Overlap {
+                with_line,
+                start,
+                end,
+            } => write!(
+                f,
+                "source range overlaps line {} on {}..{}; the earlier entry wins there",
+                with_line, start, end
+            ),
+            FindingKind::Overflow { field } => {
+                write!(f, "{} + count overflows a 64-bit integer", field)
+            }
+            FindingKind::IdentityGap { start, end } => write!(
+                f,
+                "{}..{} lies between entries but is unmapped, so it maps to itself",
+                start, end
+            ),
+            FindingKind::EmptyMap => write!(f, "map has no entries"),
+        }
+    }
+}
+
+fn lint_entry(entry: &GardenMapEntry, findings: &mut Vec<Finding>) {
+    if entry.count == 0 {
+        findings.push(Finding {
+            line: entry.line,
+            kind: FindingKind::EmptyRange,
+        });
+    }
+
+    for (field, start) in [
+        ("source", entry.src_start),
+        ("destination", entry.dst_start),
+    ] {
+        if start.checked_add(entry.count).is_none() {
+            findings.push(Finding {
+                line: entry.line,
+                kind: FindingKind::Overflow { field },
+            });
+        }
+    }
+}
+
+fn lint_map(map: &AlmanacMap, findings: &mut Vec<Finding>) {
+    if map.entries.is_empty() {
+        findings.push(Finding {
+            line: map.line,
+            kind: FindingKind::EmptyMap,
+        });
+        return;
+    }
+
+    for entry in &map.entries {
+        lint_entry(entry, findings);
+    }
+
+    // Entries that overflow have no meaningful range, and are already reported.
+    let entries = map
+        .entries
+        .iter()
+        .filter(|e| e.count > 0 && e.src_start.checked_add(e.count).is_some());
+
+    // Duplicates are reported once and then ignored, so they don't also show up as overlaps.
+    let mut unique: Vec<&GardenMapEntry> = vec![];
+    for entry in entries {
+        let duplicate_of = unique.iter().find(|other| {
+            other.src_start == entry.src_start
+                && other.dst_start == entry.dst_start
+                && other.count == entry.count
+        });
+        if let Some(original) = duplicate_of {
+            findings.push(Finding {
+                line: entry.line,
+                kind: FindingKind::Duplicate {
+                    of_line: original.line,
+                },
+            });
+            continue;
+        }
+
+        for earlier in &unique {
+            let start = earlier.src_start.max(entry.src_start);
+            let end = (earlier.src_start + earlier.count).min(entry.src_start + entry.count);
+            if start < end {
+                findings.push(Finding {
+                    line: entry.line,
+                    kind: FindingKind::Overlap {
+                        with_line: earlier.line,
+                        start,
+                        end,
+                    },
+                });
+            }
+        }
+        unique.push(entry);
+    }
+
+    // Walk the source ranges in order looking for holes between them.
+    unique.sort_by_key(|e| e.src_start);
+    let mut covered_to = unique.first().map_or(0, |e| e.src_start);
+    for entry in unique {
+        if entry.src_start > covered_to {
+            findings.push(Finding {
+                line: map.line,
+                kind: FindingKind::IdentityGap {
+                    start: covered_to,
+                    end: entry.src_start,
+                },
+            });
+        }
+        covered_to = covered_to.max(entry.src_start + entry.count);
+    }
+}
+
+// Checks every map in the almanac, returning findings in line order.
+pub fn lint(almanac: &Almanac) -> Vec<Finding> {
+    let mut findings = vec![];
+    for map in &almanac.maps {
+        lint_map(map, &mut findings);
+    }
+    findings.sort_by_key(|f| f.line);
+
+    findings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lint_test() {
+        let lines: Vec<String> = "seeds: 1 2
+seed-to-soil map:
+50 98 2
+52 50 48
+52 50 48
+0 60 10
+7 0 0
+3 120 5
+soil-to-water map:
+9223372036854775800 0 100
+water-to-location map:"
+            .lines()
+            .map(String::from)
+            .collect();
+        let almanac = Almanac::parse(&lines).unwrap();
+
+        let findings = lint(&almanac);
+        assert_eq!(
+            findings,
+            vec![
+                Finding {
+                    line: 2,
+                    kind: FindingKind::IdentityGap {
+                        start: 100,
+                        end: 120
+                    }
+                },
+                Finding {
+                    line: 5,
+                    kind: FindingKind::Duplicate { of_line: 4 }
+                },
+                Finding {
+                    line: 6,
+                    kind: FindingKind::Overlap {
+                        with_line: 4,
+                        start: 60,
+                        end: 70
+                    }
+                },
+                Finding {
+                    line: 7,
+                    kind: FindingKind::EmptyRange
+                },
+                Finding {
+                    line: 10,
+                    kind: FindingKind::Overflow {
+                        field: "destination"
+                    }
+                },
+                Finding {
+                    line: 11,
+                    kind: FindingKind::EmptyMap
+                },
+            ]
+        );
+        assert_eq!(
+            findings[2].to_string(),
+            "line 6: warning: source range overlaps line 4 on 60..70; the earlier entry wins there"
+        );
+    }
+
+    #[test]
+    fn clean_almanac_test() {
+        let lines: Vec<String> = "seeds: 79 14 55 13
+seed-to-soil map:
+50 98 2
+52 50 48"
+            .lines()
+            .map(String::from)
+            .collect();
+        let almanac = Almanac::parse(&lines).unwrap();
+
+        assert!(lint(&almanac).is_empty());
+    }
+}
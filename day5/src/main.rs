@@ -1,270 +1,150 @@
-use regex::Regex;
+mod almanac;
+mod lint;
+mod rangemap;
+
+use almanac::{Almanac, AlmanacError};
 use std::fs::File;
 use std::io::{BufRead, BufReader};
 use std::ops::Range;
 
-#[derive(Clone, Copy, Debug, PartialEq)]
-struct GardenMapEntry {
-    src_start: i64,
-    dst_start: i64,
-    count: i64,
+// Almanac::parse rejects an empty seed list, so there is always a minimum.
+fn part1(almanac: &Almanac) -> Result<i64, AlmanacError> {
+    let seed_to_location = almanac.seed_to_location()?;
+    Ok(almanac
+        .seeds
+        .iter()
+        .map(|&seed| seed_to_location.apply(seed))
+        .min()
+        .unwrap())
 }
 
-fn remap_range(
-    remap_range: &Range<i64>,
-    src_range: &Range<i64>,
-    dst_range: &Range<i64>,
-) -> (Option<Range<i64>>, Vec<Range<i64>>) {
-    if src_range.start <= remap_range.start && src_range.end >= remap_range.end {
-        // Src range completely encloses remap range - remap this whole range, there's no remainder.
-        let offset = dst_range.start - src_range.start;
-        let remapped = (remap_range.start + offset)..(remap_range.end + offset);
-        return (Some(remapped), vec![]);
-    } else if remap_range.start <= src_range.start && remap_range.end >= src_range.end {
-        // Remap range completely encloses remap range - need to split the remap range in 3.
-        let remainders = [
-            remap_range.start..src_range.start,
-            src_range.end..remap_range.end,
-        ]
-        .into_iter()
-        .filter(|x| x.start != x.end)
-        .collect();
-        return (Some(dst_range.clone()), remainders);
-    } else if remap_range.start < src_range.start && remap_range.end > src_range.start {
-        // Partial overlap, source range is higher
-        let overlap_count = remap_range.end - src_range.start;
-        let remapped = dst_range.start..(dst_range.start + overlap_count);
-
-        if remap_range.start != src_range.start {
-            return (Some(remapped), vec![remap_range.start..src_range.start]);
-        } else {
-            return (Some(remapped), vec![]);
-        }
-    } else if src_range.start < remap_range.start && src_range.end > remap_range.start {
-        // Partial overlap, source range is lower
-        let overlap_count = dst_range.start - src_range.start;
-        let remapped = (remap_range.start + overlap_count)..dst_range.end;
-        if src_range.end != remap_range.end {
-            return (Some(remapped), vec![src_range.end..remap_range.end]);
-        } else {
-            return (Some(remapped), vec![]);
-        }
-    } else {
-        // No overlap
-        return (None, vec![remap_range.clone()]);
-    }
+// Every seed range is non-empty, so neither are their images.
+fn part2(almanac: &Almanac) -> Result<i64, AlmanacError> {
+    Ok(almanac
+        .seed_to_location()?
+        .apply_ranges(&almanac.seed_ranges())
+        .iter()
+        .map(|r| r.start)
+        .min()
+        .unwrap())
 }
 
-fn apply_map_entry(
-    src_range: &Range<i64>,
-    dst_range: &Range<i64>,
-    seed_ranges: &Vec<Range<i64>>,
-) -> (Vec<Range<i64>>, Vec<Range<i64>>) {
-    let mut unmoved = vec![];
-    let mut moved = vec![];
-    let mut ranges = seed_ranges.clone();
-
-    while !ranges.is_empty() {
-        let cur_range = ranges.pop().unwrap();
-        let (remapped, mut remainders) = remap_range(&cur_range, &src_range, &dst_range);
-
-        unmoved.append(&mut remainders);
-        if let Some(new_range) = remapped {
-            if new_range.start == 0 || new_range.contains(&1459646077) {
-                println!(
-                    "Map Entry {:?} -> {:?} moved {:?} -> {:?}",
-                    src_range, dst_range, cur_range, new_range
-                );
-            }
-            moved.push(new_range);
-        }
-    }
-
-    println!(
-        "Map Entry {:?} -> {:?} processed {:?}. Moved {:?}, Unmoved {:?}",
-        src_range, dst_range, seed_ranges, moved, unmoved
-    );
-
-    return (moved, unmoved);
+// Part 2 worked backwards from the lowest locations instead of forwards from the seeds.
+fn part2_backward(almanac: &Almanac) -> Result<Option<i64>, AlmanacError> {
+    Ok(almanac
+        .seed_to_location()?
+        .lowest_image(&almanac.seed_ranges()))
 }
 
-fn apply_map(
-    map: &Vec<(Range<i64>, Range<i64>)>,
-    seed_ranges: &Vec<Range<i64>>,
-) -> Vec<Range<i64>> {
-    let mut to_process = seed_ranges.clone();
-    let mut result = vec![];
-
-    for map_entry in map {
-        let (mut moved, unmoved) = apply_map_entry(&map_entry.1, &map_entry.0, &to_process);
-
-        // Check whether the next map entry moves any of the ranges that weren't moved by
-        // this entry.
-        to_process = unmoved;
-
-        result.append(&mut moved);
-    }
-
-    result.append(&mut to_process);
-
-    return result;
+fn parse_value(s: &str) -> i64 {
+    s.parse::<i64>()
+        .unwrap_or_else(|_| exit_with(format!("'{}' is not a number", s)))
 }
 
-fn part2_parse(lines: &Vec<String>) -> (Vec<Range<i64>>, Vec<Vec<(Range<i64>, Range<i64>)>>) {
-    let seeds_regex = Regex::new(r"seeds: (?<seeds>.*)").unwrap();
-    let map_delim_regex = Regex::new(r".* map:").unwrap();
-    let map_entry_regex = Regex::new(r"(?<dst_start>\d+) (?<src_start>\d+) (?<count>\d+)").unwrap();
-
-    let mut seed_ranges: Vec<Range<i64>> = vec![];
-    let mut maps: Vec<Vec<(Range<i64>, Range<i64>)>> = vec![];
-    let mut cur_map: Vec<(Range<i64>, Range<i64>)> = vec![];
-
-    for line in lines {
-        if let Some(caps) = seeds_regex.captures(line) {
-            let tmp: Vec<i64> = caps["seeds"]
-                .split_whitespace()
-                .map(|s| s.parse().unwrap())
-                .collect();
-            seed_ranges = tmp.chunks(2).map(|s| s[0]..(s[0] + s[1])).collect();
-        } else if let Some(_caps) = map_delim_regex.captures(line) {
-            if !cur_map.is_empty() {
-                maps.push(cur_map);
-                cur_map = vec![];
-            }
-        } else if let Some(caps) = map_entry_regex.captures(line) {
-            let src_start = caps["src_start"].parse().unwrap();
-            let dst_start = caps["dst_start"].parse().unwrap();
-            let count: i64 = caps["count"].parse().unwrap();
-
-            cur_map.push((
-                dst_start..(dst_start + count),
-                src_start..(src_start + count),
-            ));
-        }
-    }
-    maps.push(cur_map);
+fn format_ranges(ranges: &[Range<i64>]) -> String {
+    let ranges: Vec<String> = ranges.iter().map(|r| format!("{:?}", r)).collect();
+    format!("[{}]", ranges.join(", "))
+}
 
-    return (seed_ranges, maps);
+fn exit_with(err: impl std::fmt::Display) -> ! {
+    eprintln!("{}", err);
+    std::process::exit(1);
 }
 
-fn part2(lines: &Vec<String>) -> i64 {
-    let (seed_ranges, maps) = part2_parse(lines);
+const USAGE: &str = "usage: day5 [--input FILE] [COMMAND]
 
-    let mut min_location = i64::MAX;
-    for seed_range in seed_ranges {
-        println!("New seeds {:?}", seed_range);
-        let mut cur_ranges = vec![seed_range];
+  (none)                   print the part 1 and part 2 answers
+  locate START END         list the seeds whose location is in START..END
+  lowest                   find the part 2 answer by searching backwards from the locations
+  convert FROM TO VALUE..  convert values between any two categories, e.g. soil humidity 81
+  lint                     report overlapping, duplicate, empty or overflowing map entries
+                           and unmapped gaps, exiting with status 1 if there are warnings";
 
-        for map in &maps {
-            println!("New map, ranges {:?}", cur_ranges);
-            cur_ranges = apply_map(map, &cur_ranges);
+fn main() {
+    let mut input = String::from("input");
+    let mut command: Vec<String> = vec![];
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--input" => input = args.next().unwrap_or_else(|| exit_with(USAGE)),
+            "-h" | "--help" => exit_with(USAGE),
+            _ => command.push(arg),
         }
-
-        cur_ranges.sort_by(|a, b| a.start.partial_cmp(&b.start).unwrap());
-        println!("Result: {:?}", cur_ranges);
-        min_location = std::cmp::min(min_location, cur_ranges[0].start);
     }
 
-    return min_location;
-}
+    let file = File::open(&input).unwrap_or_else(|err| exit_with(format!("{}: {}", input, err)));
+    let reader = BufReader::new(file);
+    let lines: Vec<String> = reader.lines().map(|l| l.unwrap()).collect();
 
-fn part1(lines: &Vec<String>) -> i64 {
-    let seeds_regex = Regex::new(r"seeds: (?<seeds>.*)").unwrap();
-    let map_delim_regex = Regex::new(r".* map:").unwrap();
-    let map_entry_regex = Regex::new(r"(?<dst_start>\d+) (?<src_start>\d+) (?<count>\d+)").unwrap();
+    let almanac = Almanac::parse(&lines).unwrap_or_else(|err| exit_with(err));
 
-    let mut seeds: Vec<i64> = vec![];
-    let mut maps: Vec<Vec<GardenMapEntry>> = vec![];
-    let mut cur_map: Vec<GardenMapEntry> = vec![];
+    let command: Vec<&str> = command.iter().map(|s| s.as_str()).collect();
+    match command.as_slice() {
+        [] => {
+            let pt1_result = part1(&almanac).unwrap_or_else(|err| exit_with(err));
+            let pt2_result = part2(&almanac).unwrap_or_else(|err| exit_with(err));
 
-    for line in lines {
-        if let Some(caps) = seeds_regex.captures(line) {
-            seeds = caps["seeds"]
-                .split_whitespace()
-                .map(|s| s.parse().unwrap())
-                .collect();
-        } else if let Some(_caps) = map_delim_regex.captures(line) {
-            if !cur_map.is_empty() {
-                maps.push(cur_map);
-                cur_map = vec![];
-            }
-        } else if let Some(caps) = map_entry_regex.captures(line) {
-            cur_map.push(GardenMapEntry {
-                src_start: caps["src_start"].parse().unwrap(),
-                dst_start: caps["dst_start"].parse().unwrap(),
-                count: caps["count"].parse().unwrap(),
-            });
+            println!("Part 1: {}, Part 2: {}", pt1_result, pt2_result);
         }
-    }
-    maps.push(cur_map);
-
-    let mut min_location = i64::MAX;
-    for seed in seeds {
-        let mut cur_idx = seed;
-        for map in &maps {
-            let entry = map
+        ["locate", start, end] => {
+            let locations = parse_value(start)..parse_value(end);
+            let seeds = almanac
+                .seeds_for_locations(&locations)
+                .unwrap_or_else(|err| exit_with(err));
+            let listed = rangemap::intersect(&seeds, &almanac.seed_ranges());
+            println!("Seeds reaching {:?}: {}", locations, format_ranges(&seeds));
+            println!("Of which listed in the almanac: {}", format_ranges(&listed));
+        }
+        ["lowest"] => match part2_backward(&almanac).unwrap_or_else(|err| exit_with(err)) {
+            Some(location) => {
+                let forward = part2(&almanac).unwrap_or_else(|err| exit_with(err));
+                println!(
+                    "Lowest location with a valid seed: {} ({} forward result {})",
+                    location,
+                    if location == forward {
+                        "matches"
+                    } else {
+                        "DOES NOT match"
+                    },
+                    forward
+                );
+            }
+            None => println!("No location has a valid seed"),
+        },
+        ["lint"] => {
+            let findings = lint::lint(&almanac);
+            for finding in &findings {
+                println!("{}", finding);
+            }
+            if findings
                 .iter()
-                .find(|&&e| cur_idx >= e.src_start && cur_idx < e.src_start + e.count);
-            cur_idx = entry
-                .map(|e| (cur_idx - e.src_start) + e.dst_start)
-                .unwrap_or(cur_idx);
+                .any(|f| f.severity() == lint::Severity::Warning)
+            {
+                std::process::exit(1);
+            }
         }
-        min_location = std::cmp::min(cur_idx, min_location);
+        ["convert", from, to, values @ ..] => {
+            let path = almanac.path(from, to).unwrap_or_else(|err| exit_with(err));
+            let mut names = vec![from.to_string()];
+            names.extend(path.iter().map(|&i| almanac.maps[i].destination.clone()));
+            println!("{}", names.join(" -> "));
+
+            let conversion = almanac
+                .conversion(from, to)
+                .unwrap_or_else(|err| exit_with(err));
+            for value in values {
+                let value = parse_value(value);
+                println!("{} {} -> {} {}", from, value, to, conversion.apply(value));
+            }
+        }
+        _ => exit_with(USAGE),
     }
-
-    return min_location;
-}
-
-fn main() {
-    let file = File::open("input").unwrap();
-    let reader = BufReader::new(file);
-    let lines: Vec<String> = reader.lines().map(|l| l.unwrap()).collect();
-
-    let pt1_result = part1(&lines);
-    let pt2_result = part2(&lines);
-
-    println!("Part 1: {}, Part 2: {}", pt1_result, pt2_result);
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    #[test]
-    fn remap_range_no_overlap() {
-        let (remapped, remainders) = remap_range(&(0..4), &(5..7), &(8..10));
-        assert_eq!(remapped, None);
-        assert_eq!(remainders, vec![(0..4)]);
-    }
-
-    #[test]
-    fn remap_range_src_encloses() {
-        let (remapped, remainders) = remap_range(&(1..3), &(0..4), &(5..9));
-        assert_eq!(remapped, Some(6..8));
-        assert!(remainders.is_empty());
-    }
-
-    #[test]
-    fn remap_range_remap_encloses() {
-        let (remapped, remainders) = remap_range(&(0..4), &(1..3), &(5..7));
-        assert_eq!(remapped, Some(5..7));
-        assert_eq!(remainders, vec![0..1, 3..4]);
-    }
-
-    #[test]
-    fn remap_range_partial_remap_lower() {
-        let (remapped, remainders) = remap_range(&(0..4), &(2..6), &(6..10));
-        assert_eq!(remapped, Some(6..8));
-        assert_eq!(remainders, vec![0..2]);
-    }
-
-    #[test]
-    fn remap_range_partial_remap_higher() {
-        let (remapped, remainders) = remap_range(&(2..5), &(0..4), &(6..10));
-        assert_eq!(remapped, Some(8..10));
-        assert_eq!(remainders, vec![4..5]);
-    }
-
     #[test]
     fn pt1_test() {
         let lines = vec![
@@ -296,7 +176,7 @@ mod tests {
             String::from("56 93 4"),
         ];
 
-        let result = part1(&lines);
+        let result = part1(&Almanac::parse(&lines).unwrap()).unwrap();
         assert_eq!(result, 35);
     }
 
@@ -331,7 +211,20 @@ mod tests {
             String::from("56 93 4"),
         ];
 
-        let result = part2(&lines);
+        let almanac = Almanac::parse(&lines).unwrap();
+        let result = part2(&almanac).unwrap();
         assert_eq!(result, 46);
+        assert_eq!(part2_backward(&almanac), Ok(Some(46)));
+
+        // Location 46 comes from seed 82, the only seed in that band.
+        let seeds = almanac.seeds_for_locations(&(46..47)).unwrap();
+        assert_eq!(seeds.len(), 1);
+        assert_eq!(seeds[0], 82..83);
+
+        // Seed 79 is soil 81, fertilizer 81, water 81, light 74, temperature 78, humidity 78.
+        assert_eq!(
+            almanac.conversion("soil", "humidity").unwrap().apply(81),
+            78
+        );
     }
 }
@@ -0,0 +1,406 @@
+use std::ops::Range;
+
+// Maps every value in start..end to value + offset.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Segment {
+    pub start: i64,
+    pub end: i64,
+    pub offset: i64,
+}
+
+// A piecewise-linear map over i64, stored as sorted, non-overlapping segments. Values outside every
+// segment map to themselves.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RangeMap {
+    segments: Vec<Segment>,
+}
+
+impl RangeMap {
+    pub fn identity() -> RangeMap {
+        RangeMap::default()
+    }
+
+    // Builds a map from almanac-style (src, dst, count) entries. Where entries overlap, the earlier
+    // entry wins, matching a first-match lookup over the entries in order. Entries must be
+    // non-negative with src + count and dst + count within i64, which keeps every offset and
+    // image in range for the other operations.
+    pub fn from_entries(entries: impl IntoIterator<Item = (i64, i64, i64)>) -> RangeMap {
+        let mut segments: Vec<Segment> = vec![];
+
+        for (src_start, dst_start, count) in entries {
+            // Only the parts of this entry not already claimed by an earlier entry are added.
+            let entry = src_start..(src_start + count);
+            let uncovered = segments.iter().fold(vec![entry], |ranges, seg| {
+                ranges
+                    .into_iter()
+                    .flat_map(|r| [r.start..r.end.min(seg.start), r.start.max(seg.end)..r.end])
+                    .filter(|r| r.start < r.end)
+                    .collect()
+            });
+
+            segments.extend(uncovered.into_iter().map(|r| Segment {
+                start: r.start,
+                end: r.end,
+                offset: dst_start - src_start,
+            }));
+        }
+
+        RangeMap::normalized(segments)
+    }
+
+    // Sorts the segments, drops identity segments and merges touching segments with the same
+    // offset.
+    fn normalized(mut segments: Vec<Segment>) -> RangeMap {
+        segments.sort_by_key(|s| s.start);
+
+        let mut merged: Vec<Segment> = vec![];
+        for seg in segments {
+            if seg.offset == 0 || seg.start >= seg.end {
+                continue;
+            }
+            match merged.last_mut() {
+                Some(last) if last.end == seg.start && last.offset == seg.offset => {
+                    last.end = seg.end
+                }
+                _ => merged.push(seg),
+            }
+        }
+
+        RangeMap { segments: merged }
+    }
+
+    // The segments plus the identity gaps between them, covering the whole of i64.
+    fn pieces(&self) -> Vec<Segment> {
+        let mut pieces = vec![];
+        let mut next_start = i64::MIN;
+        for seg in &self.segments {
+            if next_start < seg.start {
+                pieces.push(Segment {
+                    start: next_start,
+                    end: seg.start,
+                    offset: 0,
+                });
+            }
+            pieces.push(*seg);
+            next_start = seg.end;
+        }
+        pieces.push(Segment {
+            start: next_start,
+            end: i64::MAX,
+            offset: 0,
+        });
+
+        pieces
+    }
+
+    fn segment_containing(&self, value: i64) -> Option<&Segment> {
+        let idx = self.segments.partition_point(|s| s.end <= value);
+        self.segments.get(idx).filter(|s| s.start <= value)
+    }
+
+    pub fn apply(&self, value: i64) -> i64 {
+        match self.segment_containing(value) {
+            Some(seg) => value + seg.offset,
+            None => value,
+        }
+    }
+
+    // Maps each input range, splitting it wherever it crosses a segment boundary. The output is
+    // coalesced, so applying maps stage by stage doesn't fragment the range list.
+    pub fn apply_ranges(&self, ranges: &[Range<i64>]) -> Vec<Range<i64>> {
+        let pieces = self.pieces();
+        let mut result = vec![];
+
+        for range in ranges {
+            let first = pieces.partition_point(|p| p.end <= range.start);
+            for piece in pieces[first..].iter().take_while(|p| p.start < range.end) {
+                let start = range.start.max(piece.start);
+                let end = range.end.min(piece.end);
+                if start < end {
+                    result.push((start + piece.offset)..(end + piece.offset));
+                }
+            }
+        }
+
+        coalesce(result)
+    }
+
+    // All values that map into target, as sorted ranges.
+    pub fn preimage(&self, target: &Range<i64>) -> Vec<Range<i64>> {
+        let result: Vec<Range<i64>> = self
+            .pieces()
+            .iter()
+            .filter_map(|piece| {
+                let start = (piece.start + piece.offset).max(target.start);
+                let end = (piece.end + piece.offset).min(target.end);
+                (start < end).then(|| (start - piece.offset)..(end - piece.offset))
+            })
+            .collect();
+
+        coalesce(result)
+    }
+
+    // Finds the lowest value that any point of domain maps to, working backwards from the lowest
+    // images: pieces are visited in order of where their image starts, and the search stops once
+    // no remaining piece can beat the best value found.
+    pub fn lowest_image(&self, domain: &[Range<i64>]) -> Option<i64> {
+        let mut pieces = self.pieces();
+        pieces.sort_by_key(|p| p.start.saturating_add(p.offset));
+
+        let mut best: Option<i64> = None;
+        for piece in pieces {
+            let image_start = piece.start.saturating_add(piece.offset);
+            if best.is_some_and(|b| image_start >= b) {
+                break;
+            }
+
+            let lowest_hit = domain
+                .iter()
+                .map(|r| (r.start.max(piece.start), r.end.min(piece.end)))
+                .filter(|(start, end)| start < end)
+                .map(|(start, _)| start)
+                .min();
+            if let Some(lowest) = lowest_hit.map(|start| start + piece.offset) {
+                best = Some(best.map_or(lowest, |b| b.min(lowest)));
+            }
+        }
+
+        best
+    }
+
+    // Returns the map equivalent to applying self and then next.
+    pub fn compose(&self, next: &RangeMap) -> RangeMap {
+        let next_pieces = next.pieces();
+        let mut segments = vec![];
+
+        for piece in self.pieces() {
+            // Split this piece wherever its image crosses a boundary of the next map.
+            let image_start = piece.start + piece.offset;
+            let image_end = piece.end + piece.offset;
+            let first = next_pieces.partition_point(|p| p.end <= image_start);
+            for next_piece in next_pieces[first..]
+                .iter()
+                .take_while(|p| p.start < image_end)
+            {
+                let start = image_start.max(next_piece.start);
+                let end = image_end.min(next_piece.end);
+                segments.push(Segment {
+                    start: start - piece.offset,
+                    end: end - piece.offset,
+                    offset: piece.offset + next_piece.offset,
+                });
+            }
+        }
+
+        RangeMap::normalized(segments)
+    }
+}
+
+// Sorts the ranges and merges any that overlap or touch, dropping empty ranges.
+pub fn coalesce(mut ranges: Vec<Range<i64>>) -> Vec<Range<i64>> {
+    ranges.sort_by_key(|r| r.start);
+
+    let mut result: Vec<Range<i64>> = vec![];
+    for range in ranges.into_iter().filter(|r| r.start < r.end) {
+        match result.last_mut() {
+            Some(last) if range.start <= last.end => last.end = last.end.max(range.end),
+            _ => result.push(range),
+        }
+    }
+
+    result
+}
+
+// The values covered by both a and b, as sorted, coalesced ranges.
+pub fn intersect(a: &[Range<i64>], b: &[Range<i64>]) -> Vec<Range<i64>> {
+    coalesce(
+        a.iter()
+            .flat_map(|x| b.iter().map(|y| x.start.max(y.start)..x.end.min(y.end)))
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn seed_to_soil() -> RangeMap {
+        RangeMap::from_entries([(98, 50, 2), (50, 52, 48)])
+    }
+
+    fn soil_to_fertilizer() -> RangeMap {
+        RangeMap::from_entries([(15, 0, 37), (52, 37, 2), (0, 39, 15)])
+    }
+
+    #[test]
+    fn apply_test() {
+        let map = seed_to_soil();
+        assert_eq!(map.apply(0), 0);
+        assert_eq!(map.apply(50), 52);
+        assert_eq!(map.apply(97), 99);
+        assert_eq!(map.apply(98), 50);
+        assert_eq!(map.apply(99), 51);
+        assert_eq!(map.apply(100), 100);
+    }
+
+    #[test]
+    fn overlapping_entries_test() {
+        // The second entry only applies where the first doesn't.
+        let map = RangeMap::from_entries([(10, 100, 5), (8, 200, 10)]);
+        assert_eq!(map.apply(9), 201);
+        assert_eq!(map.apply(12), 102);
+        assert_eq!(map.apply(16), 208);
+        assert_eq!(map.apply(18), 18);
+    }
+
+    #[test]
+    fn apply_ranges_test() {
+        let map = seed_to_soil();
+        // 40..50 maps to itself and 50..60 to 52..62, which merge with 98..100 -> 50..52.
+        let result = map.apply_ranges(&[40..60, 97..101]);
+        assert_eq!(result, vec![40..62, 99..101]);
+    }
+
+    #[test]
+    fn compose_test() {
+        let first = seed_to_soil();
+        let second = soil_to_fertilizer();
+        let composed = first.compose(&second);
+
+        for value in -5..120 {
+            assert_eq!(composed.apply(value), second.apply(first.apply(value)));
+        }
+        assert_eq!(RangeMap::identity().compose(&first), first);
+        assert_eq!(first.compose(&RangeMap::identity()), first);
+    }
+
+    #[test]
+    fn preimage_test() {
+        let map = seed_to_soil().compose(&soil_to_fertilizer());
+
+        // Check against a pointwise scan of a domain wide enough to cover every segment.
+        for target in [0..10, 37..40, 50..60, 95..105] {
+            let preimage = map.preimage(&target);
+            for value in -10..200 {
+                let in_preimage = preimage.iter().any(|r| r.contains(&value));
+                assert_eq!(in_preimage, target.contains(&map.apply(value)), "{}", value);
+            }
+        }
+    }
+
+    #[test]
+    fn lowest_image_test() {
+        let map = seed_to_soil().compose(&soil_to_fertilizer());
+
+        for domain in [
+            vec![79..93, 55..68],
+            vec![0..1, 120..130],
+            vec![40..60, 98..100],
+            vec![],
+        ] {
+            let expected = domain
+                .iter()
+                .flat_map(|r| r.clone())
+                .map(|v| map.apply(v))
+                .min();
+            assert_eq!(map.lowest_image(&domain), expected);
+        }
+    }
+
+    #[test]
+    fn coalesce_test() {
+        assert_eq!(
+            coalesce(vec![5..8, 0..2, 2..3, 7..10, 4..4, 12..13]),
+            vec![0..3, 5..10, 12..13]
+        );
+        assert!(coalesce(vec![]).is_empty());
+    }
+
+    // The part 1 semantics: the first entry whose source range contains the value wins.
+    fn pointwise(entries: &[(i64, i64, i64)], value: i64) -> i64 {
+        entries
+            .iter()
+            .find(|&&(src, _, count)| value >= src && value < src + count)
+            .map_or(value, |&(src, dst, _)| value - src + dst)
+    }
+
+    // Every (src, dst, count) entry over a small domain.
+    fn small_entries() -> Vec<(i64, i64, i64)> {
+        let mut entries = vec![];
+        for src in 0..5 {
+            for dst in 0..5 {
+                for count in 0..4 {
+                    entries.push((src, dst, count));
+                }
+            }
+        }
+
+        entries
+    }
+
+    fn is_coalesced(ranges: &[Range<i64>]) -> bool {
+        ranges.iter().all(|r| r.start < r.end) && ranges.windows(2).all(|w| w[0].end < w[1].start)
+    }
+
+    #[test]
+    fn apply_ranges_exhaustive_test() {
+        let entries = small_entries();
+        for first in &entries {
+            for second in &entries {
+                let map_entries = [*first, *second];
+                let map = RangeMap::from_entries(map_entries);
+
+                for start in 0..10 {
+                    for end in (start + 1)..=10 {
+                        let input = start..end;
+                        let mapped = map.apply_ranges(std::slice::from_ref(&input));
+                        assert!(is_coalesced(&mapped), "{:?}", mapped);
+
+                        let mut expected: Vec<i64> =
+                            (start..end).map(|v| pointwise(&map_entries, v)).collect();
+                        expected.sort();
+                        expected.dedup();
+                        let actual: Vec<i64> = mapped.iter().flat_map(|r| r.clone()).collect();
+                        assert_eq!(actual, expected, "{:?} on {}..{}", map_entries, start, end);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn compose_exhaustive_test() {
+        let entries = small_entries();
+        for first in &entries {
+            for second in &entries {
+                let composed =
+                    RangeMap::from_entries([*first]).compose(&RangeMap::from_entries([*second]));
+                for value in -2..15 {
+                    assert_eq!(
+                        composed.apply(value),
+                        pointwise(&[*second], pointwise(&[*first], value)),
+                        "{:?} then {:?} at {}",
+                        first,
+                        second,
+                        value
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn staged_matches_composed_test() {
+        let maps = [seed_to_soil(), soil_to_fertilizer()];
+        let seeds = vec![79..93, 55..68, 0..120];
+
+        let mut staged = seeds.clone();
+        for map in &maps {
+            staged = map.apply_ranges(&staged);
+            assert!(is_coalesced(&staged));
+        }
+
+        let composed = maps[0].compose(&maps[1]);
+        assert_eq!(staged, composed.apply_ranges(&seeds));
+    }
+}
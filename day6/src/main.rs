@@ -1,36 +1,140 @@
-/*
-Time:        40     82     84     92
-Distance:   233   1011   1110   1487
-*/
-
-fn part1(races: &Vec<(u64, u64)>) -> u64 {
-    let mut result = 1;
-    for race in races {
-        let race_time = race.0;
-        let record = race.1;
-        let mut win_count = 0;
-
-        for hold_time in 1..race_time {
-            let remainder = race_time - hold_time;
-            let distance = remainder * hold_time;
-
-            if distance > record {
-                win_count += 1;
+mod physics;
+mod sheet;
+mod solver;
+
+use physics::{BoatModel, Phase};
+use sheet::{Race, RaceSheet};
+use solver::{ways_to_win, SolveError};
+use std::fmt;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+
+fn part1(races: &[Race]) -> Result<u128, SolveError> {
+    races.iter().try_fold(1u128, |product, race| {
+        product
+            .checked_mul(ways_to_win(race))
+            .ok_or(SolveError::Overflow)
+    })
+}
+
+fn part2(race: &Race) -> u128 {
+    ways_to_win(race)
+}
+
+fn parse_value(s: &str) -> u128 {
+    s.parse::<u128>()
+        .unwrap_or_else(|_| exit_with(format!("'{}' is not a number", s)))
+}
+
+fn exit_with(err: impl fmt::Display) -> ! {
+    eprintln!("{}", err);
+    std::process::exit(1);
+}
+
+const USAGE: &str = "usage: day6 [--input FILE] [report [MODEL OPTIONS]]
+
+  (none)                 print the part 1 and part 2 answers
+  report                 print the best hold time, winning hold times and margin over the
+                         record for each part 1 race and the part 2 race
+
+model options for report:
+  --phase MS:ACCEL       charge at ACCEL mm/ms per ms held for the next MS ms; may be repeated
+  --accel ACCEL          after any phases, charge at ACCEL mm/ms per ms for as long as the button
+                         is held (default 1 when no phases are given)
+  --max-speed SPEED      cap the boat's speed at SPEED mm/ms
+  --penalty MS           wait MS ms after releasing the button before the boat moves";
+
+fn main() {
+    let mut input = String::from("input");
+    let mut command: Vec<String> = vec![];
+    let mut phases: Vec<Phase> = vec![];
+    let mut accel: Option<u128> = None;
+    let mut model = BoatModel::default();
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().unwrap_or_else(|| exit_with(USAGE));
+        match arg.as_str() {
+            "--input" => input = value(),
+            "--phase" => {
+                let value = value();
+                let (duration, acceleration) =
+                    value.split_once(':').unwrap_or_else(|| exit_with(USAGE));
+                phases.push(Phase {
+                    duration: Some(parse_value(duration)),
+                    acceleration: parse_value(acceleration),
+                });
             }
+            "--accel" => accel = Some(parse_value(&value())),
+            "--max-speed" => model.max_speed = Some(parse_value(&value())),
+            "--penalty" => model.penalty = parse_value(&value()),
+            "-h" | "--help" => exit_with(USAGE),
+            _ => command.push(arg),
         }
-
-        result *= win_count;
     }
+    if !phases.is_empty() || accel.is_some() {
+        if let Some(acceleration) = accel {
+            phases.push(Phase {
+                duration: None,
+                acceleration,
+            });
+        }
+        model.phases = phases;
+    }
+
+    let file = File::open(&input).unwrap_or_else(|err| exit_with(format!("{}: {}", input, err)));
+    let reader = BufReader::new(file);
+    let lines: Vec<String> = reader.lines().map(|l| l.unwrap()).collect();
+
+    let sheet = RaceSheet::parse(&lines).unwrap_or_else(|err| exit_with(err));
+    let races = sheet.races().unwrap_or_else(|err| exit_with(err));
+    let kerned = sheet.kerned_race().unwrap_or_else(|err| exit_with(err));
 
-    return result;
+    let command: Vec<&str> = command.iter().map(|s| s.as_str()).collect();
+    match command.as_slice() {
+        [] => {
+            let pt1_result = part1(&races).unwrap_or_else(|err| exit_with(err));
+            let pt2_result = part2(&kerned);
+            println!("Part 1: {}, Part 2: {}", pt1_result, pt2_result);
+        }
+        ["report"] => {
+            for (i, race) in races.iter().enumerate() {
+                println!(
+                    "Race {} (time {}, record {}): {}",
+                    i + 1,
+                    race.time,
+                    race.record,
+                    model.report(race)
+                );
+            }
+            println!(
+                "Kerned race (time {}, record {}): {}",
+                kerned.time,
+                kerned.record,
+                model.report(&kerned)
+            );
+        }
+        _ => exit_with(USAGE),
+    }
 }
 
-fn main() {
-    let races = vec![(40, 233), (82, 1011), (84, 1110), (92, 1487)];
-    let pt1_result = part1(&races);
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    let races = vec![(40828492, 233101111101487u64)];
-    let pt2_result = part1(&races);
+    #[test]
+    fn example_test() {
+        let lines = vec![
+            String::from("Time:      7  15   30"),
+            String::from("Distance:  9  40  200"),
+        ];
+        let sheet = RaceSheet::parse(&lines).unwrap();
+        assert_eq!(part1(&sheet.races().unwrap()), Ok(288));
+        assert_eq!(part2(&sheet.kerned_race().unwrap()), 71503);
 
-    println!("{}, {}", pt1_result, pt2_result);
+        let huge = Race {
+            time: u128::MAX,
+            record: 0,
+        };
+        assert_eq!(part1(&[huge, huge]), Err(SolveError::Overflow));
+    }
 }
@@ -0,0 +1,315 @@
+use crate::sheet::Race;
+use std::fmt;
+use std::ops::{Range, RangeInclusive};
+
+// A stretch of holding the button during which each ms held adds `acceleration` mm/ms of speed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Phase {
+    // None for a phase that lasts for as long as the button is held.
+    pub duration: Option<u128>,
+    pub acceleration: u128,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BoatModel {
+    // Charging phases in order. Once they run out, holding longer adds no more speed.
+    pub phases: Vec<Phase>,
+    pub max_speed: Option<u128>,
+    // Time after the button is released before the boat starts moving.
+    pub penalty: u128,
+}
+
+// The puzzle's boat: 1 mm/ms of speed per ms held, for as long as it is held.
+impl Default for BoatModel {
+    fn default() -> Self {
+        BoatModel {
+            phases: vec![Phase {
+                duration: None,
+                acceleration: 1,
+            }],
+            max_speed: None,
+            penalty: 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RaceReport {
+    // The shortest hold reaching the furthest distance.
+    pub best_hold: u128,
+    pub best_distance: u128,
+    // How far the best hold beats the record by, or None if nothing beats it.
+    pub margin: Option<u128>,
+    // Every hold time that beats the record, as disjoint runs in increasing order.
+    pub windows: Vec<RangeInclusive<u128>>,
+}
+
+impl RaceReport {
+    pub fn ways_to_win(&self) -> u128 {
+        self.windows.iter().map(|w| w.end() - w.start() + 1).sum()
+    }
+}
+
+impl fmt::Display for RaceReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "best hold {} ms travels {} mm",
+            self.best_hold, self.best_distance
+        )?;
+        match self.margin {
+            Some(margin) => {
+                let windows: Vec<String> =
+                    self.windows.iter().map(|w| format!("{:?}", w)).collect();
+                write!(
+                    f,
+                    ", beating the record by {}; wins holding {} ({} ways)",
+                    margin,
+                    windows.join(", "),
+                    self.ways_to_win()
+                )
+            }
+            None => write!(f, ", which does not beat the record"),
+        }
+    }
+}
+
+// First value in lo..hi for which pred is false, given pred holds for a prefix of the range.
+fn partition_point(mut lo: u128, mut hi: u128, pred: impl Fn(u128) -> bool) -> u128 {
+    while lo < hi {
+        let mid = lo + (hi - lo) / 2;
+        if pred(mid) {
+            lo = mid + 1;
+        } else {
+            hi = mid;
+        }
+    }
+    lo
+}
+
+impl BoatModel {
+    // Speeds and distances saturate rather than overflow.
+    pub fn speed(&self, hold_time: u128) -> u128 {
+        let mut speed: u128 = 0;
+        let mut remaining = hold_time;
+        for phase in &self.phases {
+            let held = phase.duration.map_or(remaining, |d| d.min(remaining));
+            speed = speed.saturating_add(held.saturating_mul(phase.acceleration));
+            remaining -= held;
+        }
+        self.max_speed.map_or(speed, |max| speed.min(max))
+    }
+
+    pub fn distance(&self, race_time: u128, hold_time: u128) -> u128 {
+        let moving = race_time
+            .saturating_sub(hold_time)
+            .saturating_sub(self.penalty);
+        self.speed(hold_time).saturating_mul(moving)
+    }
+
+    // Splits the hold times 0..limit into runs over which speed grows linearly, ending each run
+    // where a phase ends or the speed cap is hit. Distance is a concave quadratic within a run.
+    fn linear_runs(&self, limit: u128) -> Vec<Range<u128>> {
+        let mut breaks = vec![0, limit];
+        let mut start: u128 = 0;
+        for phase in &self.phases {
+            if start >= limit {
+                break;
+            }
+            let end = phase.duration.map_or(limit, |d| start.saturating_add(d));
+
+            let speed = self.speed(start);
+            if let Some(max) = self.max_speed.filter(|&max| speed < max) {
+                if phase.acceleration > 0 {
+                    breaks.push(start.saturating_add((max - speed).div_ceil(phase.acceleration)));
+                }
+            }
+            breaks.push(end);
+            start = end;
+        }
+
+        breaks.retain(|&b| b <= limit);
+        breaks.sort();
+        breaks.dedup();
+        breaks.windows(2).map(|w| w[0]..w[1]).collect()
+    }
+
+    pub fn report(&self, race: &Race) -> RaceReport {
+        let distance = |hold_time| self.distance(race.time, hold_time);
+
+        // Holding until the boat can no longer move travels nothing, so never wins.
+        let limit = race.time.saturating_sub(self.penalty);
+
+        let mut best_hold = 0;
+        let mut best_distance = distance(0);
+        let mut windows: Vec<RangeInclusive<u128>> = vec![];
+        for run in self.linear_runs(limit) {
+            // Distance rises then falls within a run, so binary search for the peak and then for
+            // the edges of the winning stretch either side of it.
+            let peak = partition_point(run.start, run.end - 1, |h| distance(h + 1) > distance(h));
+            if distance(peak) > best_distance {
+                best_hold = peak;
+                best_distance = distance(peak);
+            }
+            if distance(peak) <= race.record {
+                continue;
+            }
+
+            let first = partition_point(run.start, peak, |h| distance(h) <= race.record);
+            let last = partition_point(peak, run.end, |h| distance(h) > race.record) - 1;
+            match windows.last_mut() {
+                Some(prev) if *prev.end() + 1 == first => *prev = *prev.start()..=last,
+                _ => windows.push(first..=last),
+            }
+        }
+
+        RaceReport {
+            best_hold,
+            best_distance,
+            margin: best_distance.checked_sub(race.record).filter(|&m| m > 0),
+            windows,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver;
+
+    // Every hold time that beats the record, found one at a time.
+    fn brute_force(model: &BoatModel, race: &Race) -> Vec<RangeInclusive<u128>> {
+        let mut windows: Vec<RangeInclusive<u128>> = vec![];
+        for hold_time in 0..=race.time {
+            if model.distance(race.time, hold_time) <= race.record {
+                continue;
+            }
+            match windows.last_mut() {
+                Some(prev) if *prev.end() + 1 == hold_time => *prev = *prev.start()..=hold_time,
+                _ => windows.push(hold_time..=hold_time),
+            }
+        }
+        windows
+    }
+
+    #[test]
+    fn default_model_test() {
+        let model = BoatModel::default();
+        for time in 0..=60 {
+            for record in 0..=(time * time / 4 + 1) {
+                let race = Race { time, record };
+                let report = model.report(&race);
+                let window = solver::winning_window(&race);
+                assert_eq!(report.windows, window.into_iter().collect::<Vec<_>>());
+                assert_eq!(report.best_hold, time / 2);
+            }
+        }
+
+        let report = model.report(&Race { time: 7, record: 9 });
+        assert_eq!(report.best_hold, 3);
+        assert_eq!(report.best_distance, 12);
+        assert_eq!(report.margin, Some(3));
+        assert_eq!(report.ways_to_win(), 4);
+        assert_eq!(
+            report.to_string(),
+            "best hold 3 ms travels 12 mm, beating the record by 3; wins holding 2..=5 (4 ways)"
+        );
+    }
+
+    #[test]
+    fn configured_model_test() {
+        // Slow then fast charging, capped at 10 mm/ms, with a 2 ms delay after release.
+        let model = BoatModel {
+            phases: vec![
+                Phase {
+                    duration: Some(3),
+                    acceleration: 1,
+                },
+                Phase {
+                    duration: None,
+                    acceleration: 3,
+                },
+            ],
+            max_speed: Some(10),
+            penalty: 2,
+        };
+        assert_eq!(
+            (0..8).map(|h| model.speed(h)).collect::<Vec<_>>(),
+            vec![0, 1, 2, 3, 6, 9, 10, 10]
+        );
+        assert_eq!(model.distance(20, 6), 120);
+        assert_eq!(model.distance(20, 19), 0);
+
+        let report = model.report(&Race {
+            time: 20,
+            record: 100,
+        });
+        assert_eq!(report.best_hold, 6);
+        assert_eq!(report.best_distance, 120);
+        assert_eq!(report.margin, Some(20));
+        assert_eq!(report.windows, vec![5..=7]);
+
+        let report = model.report(&Race {
+            time: 20,
+            record: 120,
+        });
+        assert_eq!(report.margin, None);
+        assert!(report.windows.is_empty());
+    }
+
+    #[test]
+    fn brute_force_test() {
+        // A slow first phase makes distance dip and rise again, so wins can be split in two.
+        let mut models = vec![];
+        for first in 0..3 {
+            for second in 0..4 {
+                for max_speed in [None, Some(4), Some(9)] {
+                    for penalty in [0, 1, 5] {
+                        models.push(BoatModel {
+                            phases: vec![
+                                Phase {
+                                    duration: Some(4),
+                                    acceleration: first,
+                                },
+                                Phase {
+                                    duration: Some(5),
+                                    acceleration: second,
+                                },
+                                Phase {
+                                    duration: None,
+                                    acceleration: 1,
+                                },
+                            ],
+                            max_speed,
+                            penalty,
+                        });
+                    }
+                }
+            }
+        }
+
+        for model in &models {
+            for time in 0..30 {
+                let best = (0..=time).map(|h| model.distance(time, h)).max().unwrap();
+                for record in (0..=best + 1).step_by(3) {
+                    let race = Race { time, record };
+                    let report = model.report(&race);
+                    assert_eq!(
+                        report.windows,
+                        brute_force(model, &race),
+                        "{:?} {:?}",
+                        model,
+                        race
+                    );
+                    assert_eq!(report.best_distance, best);
+                    assert_eq!(
+                        report.best_hold,
+                        (0..=time)
+                            .find(|&h| model.distance(time, h) == best)
+                            .unwrap()
+                    );
+                }
+            }
+        }
+    }
+}
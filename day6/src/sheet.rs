@@ -0,0 +1,189 @@
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Race {
+    pub time: u128,
+    pub record: u128,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum SheetError {
+    MissingLine(&'static str),
+    Malformed { line: usize },
+    NoValues { line: usize },
+    ColumnMismatch { times: usize, distances: usize },
+    Overflow { label: &'static str, value: String },
+}
+
+impl fmt::Display for SheetError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SheetError::MissingLine(label) => write!(f, "no '{}:' line in the race sheet", label),
+            SheetError::Malformed { line } => {
+                write!(f, "line {}: expected a label followed by numbers", line)
+            }
+            SheetError::NoValues { line } => write!(f, "line {}: no numbers after the label", line),
+            SheetError::ColumnMismatch { times, distances } => {
+                write!(f, "sheet has {} times but {} distances", times, distances)
+            }
+            SheetError::Overflow { label, value } => write!(
+                f,
+                "{} {} is too large to fit in a 128-bit integer",
+                label, value
+            ),
+        }
+    }
+}
+
+// The digit groups following each label on the sheet, e.g. ["40", "82", "84", "92"] for "Time:".
+pub struct RaceSheet {
+    times: Vec<String>,
+    distances: Vec<String>,
+}
+
+impl RaceSheet {
+    pub fn parse(lines: &[String]) -> Result<RaceSheet, SheetError> {
+        let mut times = None;
+        let mut distances = None;
+
+        for (i, line) in lines.iter().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let (label, values) = line
+                .split_once(':')
+                .ok_or(SheetError::Malformed { line: i + 1 })?;
+            let values: Vec<String> = values.split_whitespace().map(String::from).collect();
+            if values
+                .iter()
+                .any(|v| !v.chars().all(|c| c.is_ascii_digit()))
+            {
+                return Err(SheetError::Malformed { line: i + 1 });
+            }
+            // Otherwise the kerned race would try to parse an empty string.
+            if values.is_empty() {
+                return Err(SheetError::NoValues { line: i + 1 });
+            }
+
+            match label.trim() {
+                "Time" => times = Some(values),
+                "Distance" => distances = Some(values),
+                _ => return Err(SheetError::Malformed { line: i + 1 }),
+            }
+        }
+
+        let times = times.ok_or(SheetError::MissingLine("Time"))?;
+        let distances = distances.ok_or(SheetError::MissingLine("Distance"))?;
+        if times.len() != distances.len() {
+            return Err(SheetError::ColumnMismatch {
+                times: times.len(),
+                distances: distances.len(),
+            });
+        }
+
+        Ok(RaceSheet { times, distances })
+    }
+
+    // Part 1 reads each column as a separate race.
+    pub fn races(&self) -> Result<Vec<Race>, SheetError> {
+        self.times
+            .iter()
+            .zip(&self.distances)
+            .map(|(time, record)| {
+                Ok(Race {
+                    time: parse_value("Time", time)?,
+                    record: parse_value("Distance", record)?,
+                })
+            })
+            .collect()
+    }
+
+    // Part 2 ignores the spaces between columns, giving a single long race.
+    pub fn kerned_race(&self) -> Result<Race, SheetError> {
+        Ok(Race {
+            time: parse_value("Time", &self.times.concat())?,
+            record: parse_value("Distance", &self.distances.concat())?,
+        })
+    }
+}
+
+fn parse_value(label: &'static str, value: &str) -> Result<u128, SheetError> {
+    value.parse().map_err(|_| SheetError::Overflow {
+        label,
+        value: value.to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn example() -> Vec<String> {
+        vec![
+            String::from("Time:      7  15   30"),
+            String::from("Distance:  9  40  200"),
+        ]
+    }
+
+    #[test]
+    fn parse_test() {
+        let sheet = RaceSheet::parse(&example()).unwrap();
+        assert_eq!(
+            sheet.races().unwrap(),
+            vec![
+                Race { time: 7, record: 9 },
+                Race {
+                    time: 15,
+                    record: 40
+                },
+                Race {
+                    time: 30,
+                    record: 200
+                },
+            ]
+        );
+        assert_eq!(
+            sheet.kerned_race().unwrap(),
+            Race {
+                time: 71530,
+                record: 940200
+            }
+        );
+    }
+
+    #[test]
+    fn parse_errors_test() {
+        let overflow = vec![
+            String::from("Time:      12345678901234567890  12345678901234567890"),
+            String::from("Distance:  1234567890  1234567890"),
+        ];
+        let sheet = RaceSheet::parse(&overflow).unwrap();
+        assert!(sheet.races().is_ok());
+        assert_eq!(
+            sheet.kerned_race(),
+            Err(SheetError::Overflow {
+                label: "Time",
+                value: String::from("1234567890123456789012345678901234567890")
+            })
+        );
+
+        let mismatched = vec![String::from("Time: 1 2"), String::from("Distance: 3")];
+        assert_eq!(
+            RaceSheet::parse(&mismatched).err(),
+            Some(SheetError::ColumnMismatch {
+                times: 2,
+                distances: 1
+            })
+        );
+        let empty = vec![String::from("Time:"), String::from("Distance:")];
+        assert_eq!(
+            RaceSheet::parse(&empty).err(),
+            Some(SheetError::NoValues { line: 1 })
+        );
+        assert_eq!(
+            RaceSheet::parse(&example()[..1]).err(),
+            Some(SheetError::MissingLine("Distance"))
+        );
+    }
+}
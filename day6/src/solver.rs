@@ -0,0 +1,180 @@
+use crate::sheet::Race;
+use std::fmt;
+use std::ops::RangeInclusive;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum SolveError {
+    // Part 1 multiplies the ways to win each race, which must fit in a u128.
+    Overflow,
+}
+
+impl fmt::Display for SolveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SolveError::Overflow => {
+                write!(f, "the product of the ways to win overflows 128 bits")
+            }
+        }
+    }
+}
+
+// A distance too large for a u128 is certainly larger than the record.
+fn beats_record(race: &Race, hold_time: u128) -> bool {
+    hold_time
+        .checked_mul(race.time - hold_time)
+        .is_none_or(|distance| distance > race.record)
+}
+
+// The first hold time that beats the record, or time / 2 + 1 if none does.
+//
+// Holding for h travels h * (time - h), so h wins when h^2 - time * h + record < 0, i.e. strictly
+// between the roots (time -/+ sqrt(time^2 - 4 * record)) / 2. The integer square root gives a
+// candidate within one of the true boundary, which is then corrected by checking the inequality
+// directly.
+fn closed_form_low(race: &Race, squared: u128) -> u128 {
+    let half = race.time / 2;
+    let discriminant = match race.record.checked_mul(4) {
+        Some(four_record) if four_record < squared => squared - four_record,
+        // Either the record is at least time^2 / 4, which no hold time can beat, or 4 * record
+        // overflowed, which only happens when it is larger than any squared time.
+        _ => return half + 1,
+    };
+
+    let mut low = (race.time - discriminant.isqrt()) / 2;
+    while low > 0 && beats_record(race, low - 1) {
+        low -= 1;
+    }
+    while low <= half && !beats_record(race, low) {
+        low += 1;
+    }
+    low
+}
+
+// The same as closed_form_low, for times whose square doesn't fit in a u128. The distance rises
+// until h = time / 2, so the first winning hold time can be binary searched for.
+fn searched_low(race: &Race) -> u128 {
+    let (mut low, mut high) = (0, race.time / 2 + 1);
+    while low < high {
+        let mid = low + (high - low) / 2;
+        if beats_record(race, mid) {
+            high = mid;
+        } else {
+            low = mid + 1;
+        }
+    }
+    low
+}
+
+// The hold times that beat the record, or None if no hold time does. The window is symmetric
+// about time / 2, so only the lower end is needed.
+pub fn winning_window(race: &Race) -> Option<RangeInclusive<u128>> {
+    let low = match race.time.checked_mul(race.time) {
+        Some(squared) => closed_form_low(race, squared),
+        None => searched_low(race),
+    };
+    (low <= race.time / 2).then(|| low..=race.time - low)
+}
+
+pub fn ways_to_win(race: &Race) -> u128 {
+    winning_window(race).map_or(0, |window| window.end() - window.start() + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn brute_force(race: &Race) -> u128 {
+        (1..race.time)
+            .filter(|&hold_time| beats_record(race, hold_time))
+            .count() as u128
+    }
+
+    #[test]
+    fn example_test() {
+        let race = Race { time: 7, record: 9 };
+        assert_eq!(winning_window(&race), Some(2..=5));
+        assert_eq!(ways_to_win(&race), 4);
+
+        // The discriminant is a perfect square, so the roots 10 and 20 are exact; they only tie
+        // the record, so the correction has to step past them.
+        let race = Race {
+            time: 30,
+            record: 200,
+        };
+        assert_eq!(winning_window(&race), Some(11..=19));
+    }
+
+    #[test]
+    fn brute_force_test() {
+        // Includes every record whose discriminant is at or next to a perfect square, where the
+        // integer square root lands on or beside a root and needs correcting.
+        for time in 0..=120 {
+            for record in 0..=(time * time / 4 + 2) {
+                let race = Race { time, record };
+                assert_eq!(
+                    ways_to_win(&race),
+                    brute_force(&race),
+                    "time {} record {}",
+                    time,
+                    record
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn large_race_test() {
+        // The best hold just beats a record of half * half - 1, and nothing beats half * half.
+        let time = u64::MAX as u128 - 1;
+        let half = time / 2;
+        let race = Race {
+            time,
+            record: half * half - 1,
+        };
+        assert_eq!(winning_window(&race), Some(half..=half));
+        assert_eq!(
+            ways_to_win(&Race {
+                time,
+                record: half * half
+            }),
+            0
+        );
+
+        // Times too large to square are searched instead; both agree where either can be used.
+        let time = u64::MAX as u128;
+        for record in [
+            0,
+            1,
+            time,
+            time * 1000,
+            (time / 2) * (time - time / 2) - 1,
+            u128::MAX,
+        ] {
+            let race = Race { time, record };
+            assert_eq!(
+                closed_form_low(&race, time * time),
+                searched_low(&race),
+                "record {}",
+                record
+            );
+        }
+        let race = Race {
+            time: 1 << 100,
+            record: 0,
+        };
+        assert_eq!(ways_to_win(&race), (1 << 100) - 1);
+        let race = Race {
+            time: u128::MAX,
+            record: 0,
+        };
+        assert_eq!(winning_window(&race), Some(1..=u128::MAX - 1));
+
+        // Holding for 1 travels u128::MAX - 1, while holding for 2 overflows.
+        let race = Race {
+            time: u128::MAX,
+            record: u128::MAX,
+        };
+        assert_eq!(winning_window(&race), Some(2..=u128::MAX - 2));
+        assert_eq!(ways_to_win(&race), u128::MAX - 3);
+    }
+}
@@ -0,0 +1,106 @@
+use crate::hand::{parse_hands, Hand};
+use crate::rules::Rules;
+use std::cmp::Ordering;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+// A xorshift generator from a fixed seed, so every run sorts the same hands.
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new() -> Rng {
+        Rng(0x2545f4914f6cdd1d)
+    }
+
+    // A value in 0..bound.
+    pub fn below(&mut self, bound: usize) -> usize {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        (self.0 % bound as u64) as usize
+    }
+}
+
+// Generates n hands of five cards with bids in 1..=1000.
+pub fn generate(n: usize) -> Vec<String> {
+    let mut rng = Rng::new();
+    let labels: Vec<char> = "23456789TJQKA".chars().collect();
+    (0..n)
+        .map(|_| {
+            let hand: String = (0..5).map(|_| labels[rng.below(13)]).collect();
+            format!("{} {}", hand, rng.below(1000) + 1)
+        })
+        .collect()
+}
+
+// Orders hands by recomputing both categories, as before keys.
+fn compare_uncached(rules: &dyn Rules, a: &Hand, b: &Hand) -> Ordering {
+    rules
+        .category(&a.cards)
+        .cmp(&rules.category(&b.cards))
+        .then_with(|| a.cards.cmp(&b.cards))
+}
+
+pub struct Timings {
+    pub hands: usize,
+    // Parsing, including computing each hand's category and key.
+    pub parse: Duration,
+    // Sorting by the precomputed keys.
+    pub sort: Duration,
+    // Sorting with the category recomputed on both sides of every comparison, as before keys.
+    pub sort_uncached: Duration,
+}
+
+pub fn run(n: usize, rules: &dyn Rules) -> Timings {
+    let lines = generate(n);
+
+    let start = Instant::now();
+    let hands = parse_hands(&lines, rules).unwrap();
+    let parse = start.elapsed();
+
+    let mut sorted = hands.clone();
+    let start = Instant::now();
+    sorted.sort_unstable_by_key(|h| h.key);
+    let sort = start.elapsed();
+
+    let mut uncached = hands;
+    let start = Instant::now();
+    uncached.sort_unstable_by(|a, b| compare_uncached(rules, a, b));
+    let sort_uncached = start.elapsed();
+    black_box((sorted, uncached));
+
+    Timings {
+        hands: n,
+        parse,
+        sort,
+        sort_uncached,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::Jokers;
+
+    #[test]
+    fn generate_test() {
+        let lines = generate(1000);
+        assert_eq!(lines.len(), 1000);
+        assert_eq!(lines, generate(1000));
+        assert_eq!(parse_hands(&lines, &Jokers).unwrap().len(), 1000);
+    }
+
+    #[test]
+    fn run_test() {
+        let hands = parse_hands(&generate(2000), &Jokers).unwrap();
+        let mut sorted = hands.clone();
+        sorted.sort_unstable_by_key(|h| h.key);
+        let mut uncached = hands;
+        uncached.sort_unstable_by(|a, b| compare_uncached(&Jokers, a, b));
+
+        // Hands with equal keys are interchangeable, so compare the orders by key.
+        let keys = |hands: &[Hand]| hands.iter().map(|h| h.key).collect::<Vec<_>>();
+        assert_eq!(keys(&sorted), keys(&uncached));
+        assert_eq!(run(2000, &Jokers).hands, 2000);
+    }
+}
@@ -0,0 +1,136 @@
+use crate::hand::Hand;
+use crate::rules::{Category, Rules};
+use std::fmt::Write;
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct HandExplanation {
+    pub label: String,
+    pub category: Category,
+    // The card each wildcard counts as when classifying, if the hand has any.
+    pub wild_as: Option<char>,
+    pub rank: u32,
+    pub bid: u32,
+    pub winnings: u32,
+}
+
+// Explains hands already sorted weakest first.
+pub fn explain(ranked: &[Hand], rules: &dyn Rules) -> Vec<HandExplanation> {
+    ranked
+        .iter()
+        .enumerate()
+        .map(|(i, hand)| HandExplanation {
+            label: hand.label.clone(),
+            category: hand.category,
+            wild_as: rules
+                .wild_substitute(&hand.cards)
+                .and_then(|v| rules.card_label(v)),
+            rank: i as u32 + 1,
+            bid: hand.bid,
+            winnings: (i as u32 + 1) * hand.bid,
+        })
+        .collect()
+}
+
+pub fn format_table(explanations: &[HandExplanation]) -> String {
+    let mut out = String::new();
+    writeln!(
+        out,
+        "{:>6} {:<10} {:<16} {:>8} {:>6} {:>10}",
+        "Rank", "Hand", "Category", "Wild as", "Bid", "Winnings"
+    )
+    .unwrap();
+    for e in explanations {
+        writeln!(
+            out,
+            "{:>6} {:<10} {:<16} {:>8} {:>6} {:>10}",
+            e.rank,
+            e.label,
+            e.category.name(),
+            e.wild_as.map_or(String::from("-"), String::from),
+            e.bid,
+            e.winnings
+        )
+        .unwrap();
+    }
+
+    let total: u32 = explanations.iter().map(|e| e.winnings).sum();
+    writeln!(out, "{:>6} {:>54}", "Total", total).unwrap();
+
+    out
+}
+
+// One object per hand, in rank order. Labels are card characters, so need no escaping.
+pub fn format_json(explanations: &[HandExplanation]) -> String {
+    let hands: Vec<String> = explanations
+        .iter()
+        .map(|e| {
+            format!(
+                "  {{\"rank\": {}, \"hand\": \"{}\", \"category\": \"{}\", \"wild_as\": {}, \"bid\": {}, \"winnings\": {}}}",
+                e.rank,
+                e.label,
+                e.category.name(),
+                e.wild_as.map_or(String::from("null"), |c| format!("\"{}\"", c)),
+                e.bid,
+                e.winnings
+            )
+        })
+        .collect();
+
+    format!("[\n{}\n]\n", hands.join(",\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hand::parse_hands;
+    use crate::rules::Jokers;
+
+    fn example() -> Vec<HandExplanation> {
+        let lines = vec![
+            String::from("32T3K 765"),
+            String::from("T55J5 684"),
+            String::from("KK677 28"),
+            String::from("KTJJT 220"),
+            String::from("QQQJA 483"),
+        ];
+        let mut hands = parse_hands(&lines, &Jokers).unwrap();
+        hands.sort_unstable_by_key(|h| h.key);
+        explain(&hands, &Jokers)
+    }
+
+    #[test]
+    fn explain_test() {
+        let explanations = example();
+        let order: Vec<&str> = explanations.iter().map(|e| e.label.as_str()).collect();
+        assert_eq!(order, vec!["32T3K", "KK677", "T55J5", "QQQJA", "KTJJT"]);
+        assert_eq!(
+            explanations[4],
+            HandExplanation {
+                label: String::from("KTJJT"),
+                category: Category::FourOfAKind,
+                wild_as: Some('T'),
+                rank: 5,
+                bid: 220,
+                winnings: 1100
+            }
+        );
+        assert_eq!(explanations[0].wild_as, None);
+        assert_eq!(explanations.iter().map(|e| e.winnings).sum::<u32>(), 5905);
+    }
+
+    #[test]
+    fn format_test() {
+        let explanations = example();
+        let table = format_table(&explanations);
+        assert!(table.contains("     5 KTJJT      four of a kind          T    220       1100\n"));
+        assert!(table.ends_with(" 5905\n"));
+
+        let json = format_json(&explanations);
+        assert!(json.starts_with(
+            "[\n  {\"rank\": 1, \"hand\": \"32T3K\", \"category\": \"one pair\", \"wild_as\": null, \"bid\": 765, \"winnings\": 765},\n"
+        ));
+        assert!(json.ends_with(
+            "{\"rank\": 5, \"hand\": \"KTJJT\", \"category\": \"four of a kind\", \"wild_as\": \"T\", \"bid\": 220, \"winnings\": 1100}\n]\n"
+        ));
+    }
+}
@@ -0,0 +1,157 @@
+use crate::rules::{CardError, Category, Rules, MAX_CARDS};
+use std::fmt;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ParseError {
+    Malformed { line: usize },
+    UnknownCard { line: usize, card: String },
+    DuplicateCard { line: usize, card: String },
+    TooManyCards { line: usize },
+    WrongSize { line: usize, expected: usize },
+    BadBid { line: usize },
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::Malformed { line } => {
+                write!(f, "line {}: expected a hand followed by a bid", line)
+            }
+            ParseError::UnknownCard { line, card } => {
+                write!(f, "line {}: '{}' is not a card in this game", line, card)
+            }
+            ParseError::DuplicateCard { line, card } => {
+                write!(
+                    f,
+                    "line {}: '{}' appears more than once in the hand",
+                    line, card
+                )
+            }
+            ParseError::TooManyCards { line } => {
+                write!(
+                    f,
+                    "line {}: hands can have at most {} cards",
+                    line, MAX_CARDS
+                )
+            }
+            ParseError::WrongSize { line, expected } => {
+                write!(
+                    f,
+                    "line {}: hands must have exactly {} cards",
+                    line, expected
+                )
+            }
+            ParseError::BadBid { line } => write!(f, "line {}: bid is not a number", line),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Hand {
+    // The hand as written in the input.
+    pub label: String,
+    // Card values under the rules the hand was parsed with, in the order they were dealt.
+    pub cards: Vec<u8>,
+    pub bid: u32,
+    pub category: Category,
+    // Orders hands weakest first, computed once so sorting is an integer sort.
+    pub key: u64,
+}
+
+impl Hand {
+    pub fn new(label: &str, cards: Vec<u8>, bid: u32, rules: &dyn Rules) -> Hand {
+        let category = rules.category(&cards);
+        Hand {
+            label: label.to_string(),
+            key: rules.sort_key(category, &cards),
+            cards,
+            bid,
+            category,
+        }
+    }
+}
+
+pub fn parse_hands(lines: &[String], rules: &dyn Rules) -> Result<Vec<Hand>, ParseError> {
+    lines
+        .iter()
+        .enumerate()
+        .filter(|(_, l)| !l.trim().is_empty())
+        .map(|(i, l)| {
+            let line = i + 1;
+            let parts: Vec<&str> = l.split_whitespace().collect();
+            let [label, bid] = parts[..] else {
+                return Err(ParseError::Malformed { line });
+            };
+
+            let cards = rules.parse_cards(label).map_err(|err| match err {
+                CardError::Unknown(card) => ParseError::UnknownCard { line, card },
+                CardError::Duplicate(card) => ParseError::DuplicateCard { line, card },
+            })?;
+            if let Some(expected) = rules.hand_size().filter(|&n| n != cards.len()) {
+                return Err(ParseError::WrongSize { line, expected });
+            }
+            if cards.len() > MAX_CARDS {
+                return Err(ParseError::TooManyCards { line });
+            }
+
+            let bid = bid.parse().map_err(|_| ParseError::BadBid { line })?;
+            Ok(Hand::new(label, cards, bid, rules))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::poker::Poker;
+    use crate::rules::{Jokers, Standard};
+
+    #[test]
+    fn parse_test() {
+        let lines = vec![String::from("T55J5 684"), String::from("")];
+        let hands = parse_hands(&lines, &Standard).unwrap();
+        assert_eq!(hands.len(), 1);
+        assert_eq!(hands[0].label, "T55J5");
+        assert_eq!(hands[0].cards, vec![8, 3, 3, 9, 3]);
+        assert_eq!(hands[0].bid, 684);
+        assert_eq!(hands[0].category, Category::ThreeOfAKind);
+        assert_eq!(
+            parse_hands(&lines, &Jokers).unwrap()[0].cards,
+            vec![9, 4, 4, 0, 4]
+        );
+
+        assert_eq!(
+            parse_hands(&[String::from("T55J5")], &Standard),
+            Err(ParseError::Malformed { line: 1 })
+        );
+        assert_eq!(
+            parse_hands(&[String::from("T55X5 1")], &Standard),
+            Err(ParseError::UnknownCard {
+                line: 1,
+                card: String::from("X")
+            })
+        );
+        assert_eq!(
+            parse_hands(&[String::from("23456789TJQKA23 1")], &Standard),
+            Err(ParseError::TooManyCards { line: 1 })
+        );
+        assert_eq!(
+            parse_hands(&[String::from("AhKhQhJh 1")], &Poker),
+            Err(ParseError::WrongSize {
+                line: 1,
+                expected: 5
+            })
+        );
+        assert_eq!(
+            parse_hands(&[String::from("AhKhQhJhKh 1")], &Poker),
+            Err(ParseError::DuplicateCard {
+                line: 1,
+                card: String::from("Kh")
+            })
+        );
+        assert_eq!(
+            parse_hands(&[String::from("T55J5 -1")], &Standard),
+            Err(ParseError::BadBid { line: 1 })
+        );
+    }
+}
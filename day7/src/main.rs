@@ -1,169 +1,166 @@
-use std::cmp::Ordering;
-use std::collections::HashMap;
+mod bench;
+mod explain;
+mod hand;
+mod poker;
+mod rules;
+
+use hand::{parse_hands, Hand, ParseError};
+use rules::{Jokers, Rules, Standard};
 use std::fs::File;
 use std::io::{BufRead, BufReader};
 
-const JOKER_VAL: u8 = 1;
-
-const FIVE_OF_A_KIND: u8 = 6;
-const FOUR_OF_A_KIND: u8 = 5;
-const FULL_HOUSE: u8 = 4;
-const THREE_OF_A_KIND: u8 = 3;
-const TWO_PAIR: u8 = 2;
-const PAIR: u8 = 1;
-const HIGH_CARD: u8 = 0;
-
-#[derive(Clone, Debug, Eq, PartialEq)]
-struct Hand {
-    str: String,
-    cards: Vec<u8>,
+// The hands sorted weakest first, so each one's rank is its position plus one.
+fn ranked_hands(lines: &[String], rules: &dyn Rules) -> Result<Vec<Hand>, ParseError> {
+    let mut hands = parse_hands(lines, rules)?;
+    hands.sort_unstable_by_key(|h| h.key);
+    Ok(hands)
 }
 
-impl Hand {
-    fn get_rank(&self) -> u8 {
-        let mut card_counts: HashMap<u8, u8> = HashMap::new();
-        let mut joker_count = 0;
-        for &card in &self.cards {
-            if card == JOKER_VAL {
-                joker_count += 1;
-            } else {
-                *card_counts.entry(card).or_insert(0) += 1;
-            }
-        }
-
-        let mut tmp: Vec<_> = card_counts.iter().collect();
-        tmp.sort_by(|(a_val, a_count), (b_val, b_count)| {
-            if a_count == b_count {
-                a_val.cmp(&b_val)
-            } else {
-                b_count.cmp(&a_count)
-            }
-        });
-
-        if joker_count == 5 {
-            return FIVE_OF_A_KIND;
-        }
-
-        let highest_count = tmp[0].1;
-        let next_highest_count = if tmp.len() > 1 { tmp[1].1 } else { &0 };
-        match (highest_count, next_highest_count, joker_count) {
-            (5, _, _) => FIVE_OF_A_KIND,
-            (4, _, 1) => FIVE_OF_A_KIND,
-            (4, _, 0) => FOUR_OF_A_KIND,
-            (3, _, 2) => FIVE_OF_A_KIND,
-            (3, _, 1) => FOUR_OF_A_KIND,
-            (3, 2, _) => FULL_HOUSE,
-            (3, _, _) => THREE_OF_A_KIND,
-            (2, _, 3) => FIVE_OF_A_KIND,
-            (2, _, 2) => FOUR_OF_A_KIND,
-            (2, 2, 1) => FULL_HOUSE,
-            (2, _, 1) => THREE_OF_A_KIND,
-            (2, 2, 0) => TWO_PAIR,
-            (2, _, _) => PAIR,
-            (1, _, 4) => FIVE_OF_A_KIND,
-            (1, _, 3) => FOUR_OF_A_KIND,
-            (1, _, 2) => THREE_OF_A_KIND,
-            (1, _, 1) => PAIR,
-            _ => HIGH_CARD,
-        }
-    }
+// Sums each bid multiplied by its rank.
+fn total_winnings(lines: &[String], rules: &dyn Rules) -> Result<u32, ParseError> {
+    Ok(ranked_hands(lines, rules)?
+        .iter()
+        .enumerate()
+        .map(|(rank, hand)| (rank as u32 + 1) * hand.bid)
+        .sum())
 }
 
-impl PartialOrd for Hand {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.cmp(other))
-    }
+fn part1(lines: &[String]) -> Result<u32, ParseError> {
+    total_winnings(lines, &Standard)
 }
 
-impl Ord for Hand {
-    fn cmp(&self, other: &Self) -> Ordering {
-        let self_rank = self.get_rank();
-        let other_rank = other.get_rank();
-        let rank_cmp = self_rank.cmp(&other_rank);
-        if rank_cmp != Ordering::Equal {
-            return rank_cmp;
-        } else {
-            for i in 0..self.cards.len() {
-                let c = self.cards[i].cmp(&other.cards[i]);
-                if c != Ordering::Equal {
-                    return c;
-                }
-            }
-        }
-
-        return Ordering::Equal;
-    }
+fn part2(lines: &[String]) -> Result<u32, ParseError> {
+    total_winnings(lines, &Jokers)
 }
 
-fn parse_hands(lines: &Vec<String>) -> Vec<(Hand, u32)> {
-    return lines
-        .iter()
-        .map(|l| {
-            let parts: Vec<_> = l.split_whitespace().collect();
-            let cards = parts[0]
-                .chars()
-                .map(|c| match c {
-                    'A' => 14,
-                    'K' => 13,
-                    'Q' => 12,
-                    'J' => 1,
-                    'T' => 10,
-                    _ => c.to_digit(10).unwrap() as u8,
-                })
-                .collect();
-
-            (
-                Hand {
-                    str: parts[0].to_string(),
-                    cards: cards,
-                },
-                parts[1].parse().unwrap(),
-            )
-        })
-        .collect();
+fn exit_with(err: impl std::fmt::Display) -> ! {
+    eprintln!("{}", err);
+    std::process::exit(1);
 }
 
-fn part2(lines: &Vec<String>) -> u32 {
-    let mut hands = parse_hands(lines);
-    hands.sort_by(|(a, _), (b, _)| a.cmp(&b));
-    println!("{:?}", hands);
-    for (hand, _) in &hands {
-        println!("{:?} {}", hand, hand.get_rank());
-    }
+fn read_input(input: &str) -> Vec<String> {
+    let file = File::open(input).unwrap_or_else(|err| exit_with(format!("{}: {}", input, err)));
+    let reader = BufReader::new(file);
+    reader.lines().map(|l| l.unwrap()).collect()
+}
 
-    let mut result = 0;
-    for (rank, (_, bet)) in hands.iter().enumerate() {
-        result += (rank as u32 + 1) * bet;
-    }
+const USAGE: &str = "usage: day7 [--input FILE] [--rules standard|jokers|poker] [--json] [COMMAND]
 
-    return result;
-}
+  (none)         print the part 1 and part 2 answers, or with --rules, the total winnings
+                 under those rules. Poker hands are written with suits, e.g. AhKhQhJhTh
+  explain        list every hand in rank order with its category, the card its jokers
+                 count as, and its winnings, as a table or with --json as JSON
+                 (default rules: jokers)
+  bench [N..]    time parsing and sorting N generated hands under the joker rules,
+                 for each N given (default 10000 100000 1000000)";
 
 fn main() {
-    let file = File::open("input").unwrap();
-    let reader = BufReader::new(file);
-    let lines: Vec<String> = reader.lines().map(|l| l.unwrap()).collect();
+    let mut input = String::from("input");
+    let mut rules: Option<Box<dyn Rules>> = None;
+    let mut json = false;
+    let mut command: Vec<String> = vec![];
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--input" => input = args.next().unwrap_or_else(|| exit_with(USAGE)),
+            "--rules" => {
+                rules = Some(
+                    args.next()
+                        .and_then(|name| rules::by_name(&name))
+                        .unwrap_or_else(|| exit_with(USAGE)),
+                )
+            }
+            "--json" => json = true,
+            "-h" | "--help" => exit_with(USAGE),
+            _ => command.push(arg),
+        }
+    }
+
+    let command: Vec<&str> = command.iter().map(|s| s.as_str()).collect();
+    match command.as_slice() {
+        [] => {
+            let lines = read_input(&input);
+            if let Some(rules) = rules {
+                let total =
+                    total_winnings(&lines, rules.as_ref()).unwrap_or_else(|err| exit_with(err));
+                println!("Total winnings: {}", total);
+                return;
+            }
 
-    let pt2_result = part2(&lines);
+            let pt1_result = part1(&lines).unwrap_or_else(|err| exit_with(err));
+            let pt2_result = part2(&lines).unwrap_or_else(|err| exit_with(err));
 
-    println!("Part 2: {}", pt2_result);
+            println!("Part 1: {}, Part 2: {}", pt1_result, pt2_result);
+        }
+        ["explain"] => {
+            let rules = rules.unwrap_or_else(|| Box::new(Jokers));
+            let hands = ranked_hands(&read_input(&input), rules.as_ref())
+                .unwrap_or_else(|err| exit_with(err));
+            let explanations = explain::explain(&hands, rules.as_ref());
+            if json {
+                print!("{}", explain::format_json(&explanations));
+            } else {
+                print!("{}", explain::format_table(&explanations));
+            }
+        }
+        ["bench", sizes @ ..] => {
+            let sizes: Vec<usize> = if sizes.is_empty() {
+                vec![10_000, 100_000, 1_000_000]
+            } else {
+                sizes
+                    .iter()
+                    .map(|s| s.parse().unwrap_or_else(|_| exit_with(USAGE)))
+                    .collect()
+            };
+            for n in sizes {
+                let timings = bench::run(n, &Jokers);
+                println!(
+                    "{:>9} hands: parse {:>10.2?}, keyed sort {:>10.2?}, uncached sort {:>10.2?}",
+                    timings.hands, timings.parse, timings.sort, timings.sort_uncached
+                );
+            }
+        }
+        _ => exit_with(USAGE),
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    #[test]
-    fn pt2_test() {
-        let lines = vec![
+    fn example() -> Vec<String> {
+        vec![
             String::from("32T3K 765"),
             String::from("T55J5 684"),
             String::from("KK677 28"),
             String::from("KTJJT 220"),
             String::from("QQQJA 483"),
-        ];
+        ]
+    }
 
-        let result = part2(&lines);
-        assert_eq!(result, 5905);
+    #[test]
+    fn pt1_test() {
+        assert_eq!(part1(&example()), Ok(6440));
+    }
+
+    #[test]
+    fn pt2_test() {
+        assert_eq!(part2(&example()), Ok(5905));
+    }
+
+    #[test]
+    fn poker_test() {
+        let lines = vec![
+            String::from("AhKhQhJhTh 10"),
+            String::from("2c2d5h4s3c 20"),
+            String::from("AcAdKh4s3c 30"),
+            String::from("Ac2d3h4s5c 40"),
+            String::from("AhAsKd4c3h 50"),
+        ];
+        let hands = ranked_hands(&lines, &poker::Poker).unwrap();
+        let order: Vec<&str> = hands.iter().map(|h| h.label.as_str()).collect();
+        assert_eq!(order[0], "2c2d5h4s3c");
+        assert_eq!(&order[3..], ["Ac2d3h4s5c", "AhKhQhJhTh"]);
+        assert_eq!(hands[1].key, hands[2].key);
     }
 }
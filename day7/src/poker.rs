@@ -0,0 +1,211 @@
+use crate::rules::{classify, pack_key, CardError, Category, Rules};
+
+const RANKS: &str = "23456789TJQKA";
+const SUITS: &str = "cdhs";
+
+// Standard five card poker. Cards are written as a rank then a suit, e.g. "Th" for the ten of
+// hearts, and stored with the rank in the low four bits and the suit above it.
+pub struct Poker;
+
+fn rank(card: u8) -> u8 {
+    card & 0xf
+}
+
+fn suit(card: u8) -> u8 {
+    card >> 4
+}
+
+// The rank of the top card if the hand is a straight. Aces may also play low, below the two.
+fn straight_high(cards: &[u8]) -> Option<u8> {
+    let mut ranks: Vec<u8> = cards.iter().map(|&c| rank(c)).collect();
+    ranks.sort();
+    ranks.dedup();
+    if ranks.len() != cards.len() {
+        return None;
+    }
+
+    let ace = RANKS.len() as u8 - 1;
+    if ranks.windows(2).all(|w| w[1] == w[0] + 1) {
+        Some(*ranks.last().unwrap())
+    } else if *ranks.last().unwrap() == ace
+        && ranks[..ranks.len() - 1]
+            .iter()
+            .enumerate()
+            .all(|(i, &r)| r == i as u8)
+    {
+        Some(ranks[ranks.len() - 2])
+    } else {
+        None
+    }
+}
+
+// Ranks grouped by how many of each the hand has: (count, rank), largest groups first and higher
+// ranks first within the same size.
+fn groups(cards: &[u8]) -> Vec<(u8, u8)> {
+    let mut counts = [0u8; 16];
+    for &card in cards {
+        counts[rank(card) as usize] += 1;
+    }
+    let mut groups: Vec<(u8, u8)> = (0..16u8)
+        .filter(|&r| counts[r as usize] > 0)
+        .map(|r| (counts[r as usize], r))
+        .collect();
+    groups.sort_by(|a, b| b.cmp(a));
+    groups
+}
+
+impl Rules for Poker {
+    fn card_value(&self, label: char) -> Option<u8> {
+        RANKS.find(label).map(|i| i as u8)
+    }
+
+    fn is_wild(&self, _value: u8) -> bool {
+        false
+    }
+
+    // There is one deck, so a hand can't hold the same card twice.
+    fn parse_cards(&self, hand: &str) -> Result<Vec<u8>, CardError> {
+        let chars: Vec<char> = hand.chars().collect();
+        let mut cards = vec![];
+        for card in chars.chunks(2) {
+            let text: String = card.iter().collect();
+            let value = match card {
+                &[r, s] => match (self.card_value(r), SUITS.find(s)) {
+                    (Some(rank), Some(suit)) => rank | (suit as u8) << 4,
+                    _ => return Err(CardError::Unknown(text)),
+                },
+                _ => return Err(CardError::Unknown(text)),
+            };
+            if cards.contains(&value) {
+                return Err(CardError::Duplicate(text));
+            }
+            cards.push(value);
+        }
+        Ok(cards)
+    }
+
+    fn hand_size(&self) -> Option<usize> {
+        Some(5)
+    }
+
+    // With no repeated cards there are at most four of any rank, so five of a kind never comes up.
+    fn category(&self, cards: &[u8]) -> Category {
+        let counts: Vec<u8> = groups(cards).iter().map(|&(count, _)| count).collect();
+        let by_counts = classify(&counts, 0);
+        let flush = cards.iter().all(|&c| suit(c) == suit(cards[0]));
+        let straight = straight_high(cards).is_some();
+
+        let by_shape = match (straight, flush) {
+            (true, true) => Category::StraightFlush,
+            (false, true) => Category::Flush,
+            (true, false) => Category::Straight,
+            (false, false) => Category::HighCard,
+        };
+        by_counts.max(by_shape)
+    }
+
+    // Ties are broken on ranks alone, biggest groups first: the rank of a full house's three of a
+    // kind before its pair, and a pair before its kickers. Straights compare only their top card.
+    fn sort_key(&self, category: Category, cards: &[u8]) -> u64 {
+        match category {
+            Category::Straight | Category::StraightFlush => {
+                pack_key(category, &[straight_high(cards).unwrap()])
+            }
+            _ => {
+                let ranks: Vec<u8> = groups(cards).iter().map(|&(_, r)| r).collect();
+                pack_key(category, &ranks)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(hand: &str) -> u64 {
+        let cards = Poker.parse_cards(hand).unwrap();
+        Poker.sort_key(Poker.category(&cards), &cards)
+    }
+
+    fn category(hand: &str) -> Category {
+        Poker.category(&Poker.parse_cards(hand).unwrap())
+    }
+
+    #[test]
+    fn category_test() {
+        for (hand, expected) in [
+            ("AhKhQhJhTh", Category::StraightFlush),
+            ("5d4d3d2dAd", Category::StraightFlush),
+            ("9c9d9h9s2c", Category::FourOfAKind),
+            ("3c3d3hKsKc", Category::FullHouse),
+            ("2h7h9hJhAh", Category::Flush),
+            ("9c8dTh7sJc", Category::Straight),
+            ("Ac2d3h4s5c", Category::Straight),
+            ("QcKdAh2s3c", Category::HighCard),
+            ("7c7d7h2s3c", Category::ThreeOfAKind),
+            ("7c7d2h2s3c", Category::TwoPair),
+            ("7c7d2h4s3c", Category::Pair),
+            ("7c8d2h4s3c", Category::HighCard),
+        ] {
+            assert_eq!(category(hand), expected, "{}", hand);
+        }
+    }
+
+    #[test]
+    fn sort_key_test() {
+        // Strictly increasing.
+        let hands = [
+            "7c8d2h4s3c",
+            "7c8d2h5s3c",
+            "Ac8d2h5s3c",
+            "2c2d5h4s3c",
+            "2c2d6h4s3c",
+            "AcAd2h4s3c",
+            "AcAdKh4s3c",
+            "3c3d2h2sAc",
+            "4c4d2h2s3c",
+            "4c4d3h3s2c",
+            "7c7d7h2s3c",
+            "Ac2d3h4s5c",
+            "2c3d4h5s6c",
+            "TcJdQhKsAc",
+            "2h7h9hJhAh",
+            "3h7h9hJhAh",
+            "3c3d3hKsKc",
+            "4c4d4h2s2c",
+            "9c9d9h9s2c",
+            "9c9d9h9s3c",
+            "5d4d3d2dAd",
+            "AhKhQhJhTh",
+        ];
+        for pair in hands.windows(2) {
+            assert!(key(pair[0]) < key(pair[1]), "{} < {}", pair[0], pair[1]);
+        }
+
+        // Suits never break ties.
+        assert_eq!(key("AcAdKh4s3c"), key("AhAsKd4c3h"));
+        assert_eq!(key("2h7h9hJhAh"), key("2s7s9sJsAs"));
+    }
+
+    #[test]
+    fn parse_test() {
+        assert_eq!(Poker.parse_cards("Th2c"), Ok(vec![8 | 2 << 4, 0]));
+        assert_eq!(
+            Poker.parse_cards("Th2x"),
+            Err(CardError::Unknown(String::from("2x")))
+        );
+        assert_eq!(
+            Poker.parse_cards("Th2"),
+            Err(CardError::Unknown(String::from("2")))
+        );
+        assert_eq!(
+            Poker.parse_cards("AhAhAhAhAh"),
+            Err(CardError::Duplicate(String::from("Ah")))
+        );
+        assert_eq!(
+            Poker.parse_cards("AhAdAcAsAh"),
+            Err(CardError::Duplicate(String::from("Ah")))
+        );
+    }
+}
@@ -0,0 +1,368 @@
+use crate::poker::Poker;
+
+// Why a hand as written couldn't be split into cards, holding the offending card's text.
+#[derive(Debug, PartialEq, Eq)]
+pub enum CardError {
+    Unknown(String),
+    Duplicate(String),
+}
+
+// Hand categories from weakest to strongest.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Category {
+    HighCard,
+    Pair,
+    TwoPair,
+    ThreeOfAKind,
+    Straight,
+    Flush,
+    FullHouse,
+    FourOfAKind,
+    StraightFlush,
+    FiveOfAKind,
+}
+
+// Sort keys hold the category in the top byte and each card below it in four bits, first card
+// most significant, so comparing keys compares category and then cards in order.
+const CARD_BITS: u32 = 4;
+pub const MAX_CARDS: usize = ((u64::BITS - 8) / CARD_BITS) as usize;
+
+impl Category {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Category::HighCard => "high card",
+            Category::Pair => "one pair",
+            Category::TwoPair => "two pair",
+            Category::ThreeOfAKind => "three of a kind",
+            Category::Straight => "straight",
+            Category::Flush => "flush",
+            Category::FullHouse => "full house",
+            Category::FourOfAKind => "four of a kind",
+            Category::StraightFlush => "straight flush",
+            Category::FiveOfAKind => "five of a kind",
+        }
+    }
+}
+
+// The smallest group sizes each category needs, strongest first. A hand takes the first
+// category whose groups its own largest groups are at least as big as, so hands of any size
+// classify sensibly: six of a kind is still five of a kind, and three pairs is two pair.
+const CATEGORY_TABLE: [(&[u8], Category); 7] = [
+    (&[5], Category::FiveOfAKind),
+    (&[4], Category::FourOfAKind),
+    (&[3, 2], Category::FullHouse),
+    (&[3], Category::ThreeOfAKind),
+    (&[2, 2], Category::TwoPair),
+    (&[2], Category::Pair),
+    (&[], Category::HighCard),
+];
+
+// Classifies a hand from the sizes of its groups of matching cards, largest first, and its
+// number of wildcards. Wildcards always do best joining the largest group.
+pub fn classify(counts: &[u8], wild_count: u8) -> Category {
+    let mut counts = counts.to_vec();
+    match counts.first_mut() {
+        Some(largest) => *largest += wild_count,
+        None => counts.push(wild_count),
+    }
+
+    CATEGORY_TABLE
+        .iter()
+        .find(|(needed, _)| {
+            needed.len() <= counts.len() && needed.iter().zip(&counts).all(|(n, c)| c >= n)
+        })
+        .map(|&(_, category)| category)
+        .unwrap()
+}
+
+// How a game reads, classifies and orders hands.
+pub trait Rules {
+    // The card's strength for tie breaks, or None if the label isn't a card in this game.
+    fn card_value(&self, label: char) -> Option<u8>;
+
+    // Whether the card stands in for whichever card makes the best hand.
+    fn is_wild(&self, value: u8) -> bool;
+
+    // Splits a hand as written into card values, or returns the first card not in this game.
+    fn parse_cards(&self, hand: &str) -> Result<Vec<u8>, CardError> {
+        hand.chars()
+            .map(|c| {
+                self.card_value(c)
+                    .ok_or_else(|| CardError::Unknown(c.to_string()))
+            })
+            .collect()
+    }
+
+    // The number of cards every hand must have, if the game fixes one.
+    fn hand_size(&self) -> Option<usize> {
+        None
+    }
+
+    // The label for a card value; the inverse of card_value.
+    fn card_label(&self, value: u8) -> Option<char> {
+        (' '..='~').find(|&c| self.card_value(c) == Some(value))
+    }
+
+    // The card every wildcard in the hand is best treated as, or None if it has no wildcards.
+    // Joining the largest group is always best; on a tie the strongest such card is picked, and
+    // a hand of only wildcards becomes the strongest card in the game.
+    fn wild_substitute(&self, cards: &[u8]) -> Option<u8> {
+        if !cards.iter().any(|&c| self.is_wild(c)) {
+            return None;
+        }
+
+        let mut card_counts = [0u8; 1 << CARD_BITS];
+        for &card in cards.iter().filter(|&&c| !self.is_wild(c)) {
+            card_counts[card as usize] += 1;
+        }
+        let largest = card_counts.iter().copied().max().unwrap();
+        (0..card_counts.len() as u8).rev().find(|&v| {
+            if largest > 0 {
+                card_counts[v as usize] == largest
+            } else {
+                self.card_label(v).is_some() && !self.is_wild(v)
+            }
+        })
+    }
+
+    fn category(&self, cards: &[u8]) -> Category {
+        let mut card_counts = [0u8; 1 << CARD_BITS];
+        let mut wild_count = 0;
+        for &card in cards {
+            if self.is_wild(card) {
+                wild_count += 1;
+            } else {
+                card_counts[card as usize] += 1;
+            }
+        }
+
+        let mut counts: Vec<u8> = card_counts.into_iter().filter(|&c| c > 0).collect();
+        counts.sort_by(|a, b| b.cmp(a));
+        classify(&counts, wild_count)
+    }
+
+    // A key ordering hands weakest first: stronger category wins, then the first card that
+    // differs.
+    fn sort_key(&self, category: Category, cards: &[u8]) -> u64 {
+        pack_key(category, cards)
+    }
+}
+
+// Packs a category and up to MAX_CARDS tie-break values, each below 16, into a sort key.
+pub fn pack_key(category: Category, values: &[u8]) -> u64 {
+    values
+        .iter()
+        .enumerate()
+        .fold((category as u64) << (u64::BITS - 8), |key, (i, &value)| {
+            key | (value as u64) << (u64::BITS - 8 - CARD_BITS * (i as u32 + 1))
+        })
+}
+
+// Camel Cards as played in part 1: every card stands for itself.
+pub struct Standard;
+
+// Part 2: J is a joker, wild but the weakest card in tie breaks.
+pub struct Jokers;
+
+const STANDARD_ORDER: &str = "23456789TJQKA";
+const JOKERS_ORDER: &str = "J23456789TQKA";
+
+impl Rules for Standard {
+    fn card_value(&self, label: char) -> Option<u8> {
+        STANDARD_ORDER.find(label).map(|i| i as u8)
+    }
+
+    fn is_wild(&self, _value: u8) -> bool {
+        false
+    }
+}
+
+impl Rules for Jokers {
+    fn card_value(&self, label: char) -> Option<u8> {
+        JOKERS_ORDER.find(label).map(|i| i as u8)
+    }
+
+    fn is_wild(&self, value: u8) -> bool {
+        value == 0
+    }
+}
+
+// Looks up a rule set by the name used on the command line.
+pub fn by_name(name: &str) -> Option<Box<dyn Rules>> {
+    match name {
+        "standard" => Some(Box::new(Standard)),
+        "jokers" => Some(Box::new(Jokers)),
+        "poker" => Some(Box::new(Poker)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cards(rules: &dyn Rules, hand: &str) -> Vec<u8> {
+        hand.chars().map(|c| rules.card_value(c).unwrap()).collect()
+    }
+
+    #[test]
+    fn category_test() {
+        for (hand, standard, jokers) in [
+            ("32T3K", Category::Pair, Category::Pair),
+            ("KK677", Category::TwoPair, Category::TwoPair),
+            ("T55J5", Category::ThreeOfAKind, Category::FourOfAKind),
+            ("KTJJT", Category::TwoPair, Category::FourOfAKind),
+            ("QQQJA", Category::ThreeOfAKind, Category::FourOfAKind),
+            ("JJJJJ", Category::FiveOfAKind, Category::FiveOfAKind),
+            ("2345J", Category::HighCard, Category::Pair),
+            ("22JJ3", Category::TwoPair, Category::FourOfAKind),
+            ("2233J", Category::TwoPair, Category::FullHouse),
+        ] {
+            assert_eq!(
+                Standard.category(&cards(&Standard, hand)),
+                standard,
+                "{}",
+                hand
+            );
+            assert_eq!(Jokers.category(&cards(&Jokers, hand)), jokers, "{}", hand);
+        }
+    }
+
+    // The category of a hand with no wildcards, by its full count pattern.
+    fn reference_category(cards: &[u8]) -> Category {
+        let mut counts: Vec<usize> = (0..16)
+            .map(|v| cards.iter().filter(|&&c| c == v).count())
+            .filter(|&c| c > 0)
+            .collect();
+        counts.sort_by(|a, b| b.cmp(a));
+        match counts.as_slice() {
+            [5] => Category::FiveOfAKind,
+            [4, 1] => Category::FourOfAKind,
+            [3, 2] => Category::FullHouse,
+            [3, 1, 1] => Category::ThreeOfAKind,
+            [2, 2, 1] => Category::TwoPair,
+            [2, 1, 1, 1] => Category::Pair,
+            [1, 1, 1, 1, 1] => Category::HighCard,
+            _ => panic!("not a five card hand: {:?}", cards),
+        }
+    }
+
+    #[test]
+    fn exhaustive_test() {
+        // Five labels plus the joker cover every count pattern with every number of jokers.
+        let labels: Vec<u8> = "J23456"
+            .chars()
+            .map(|c| Jokers.card_value(c).unwrap())
+            .collect();
+        let substitutes: Vec<u8> = "234567"
+            .chars()
+            .map(|c| Jokers.card_value(c).unwrap())
+            .collect();
+
+        let mut patterns = std::collections::HashSet::new();
+        for i in 0..labels.len().pow(5) {
+            let hand: Vec<u8> = (0..5)
+                .map(|pos| labels[i / labels.len().pow(pos) % labels.len()])
+                .collect();
+            let jokers: Vec<usize> = (0..5).filter(|&pos| hand[pos] == 0).collect();
+
+            // The best hand from every way of replacing each joker with a real card.
+            let mut best = Category::HighCard;
+            for j in 0..substitutes.len().pow(jokers.len() as u32) {
+                let mut replaced = hand.clone();
+                for (k, &pos) in jokers.iter().enumerate() {
+                    replaced[pos] =
+                        substitutes[j / substitutes.len().pow(k as u32) % substitutes.len()];
+                }
+                best = best.max(reference_category(&replaced));
+            }
+
+            let mut counts: Vec<u8> =
+                hand.iter()
+                    .filter(|&&c| c != 0)
+                    .fold(vec![0; 16], |mut acc, &c| {
+                        acc[c as usize] += 1;
+                        acc
+                    });
+            counts.retain(|&c| c > 0);
+            counts.sort_by(|a, b| b.cmp(a));
+            patterns.insert((counts, jokers.len()));
+
+            assert_eq!(Jokers.category(&hand), best, "{:?}", hand);
+            if jokers.is_empty() {
+                assert_eq!(Standard.category(&hand), best, "{:?}", hand);
+            }
+        }
+
+        // Every partition of the non-joker cards, for 0 to 5 jokers.
+        assert_eq!(patterns.len(), 7 + 5 + 3 + 2 + 1 + 1);
+    }
+
+    #[test]
+    fn classify_test() {
+        assert_eq!(classify(&[], 0), Category::HighCard);
+        assert_eq!(classify(&[], 2), Category::Pair);
+        assert_eq!(classify(&[6, 1], 0), Category::FiveOfAKind);
+        assert_eq!(classify(&[2, 2, 2, 1], 0), Category::TwoPair);
+        assert_eq!(classify(&[2, 2, 2], 1), Category::FullHouse);
+        assert_eq!(classify(&[1, 1], 7), Category::FiveOfAKind);
+        assert_eq!(classify(&[1, 1, 1], 1), Category::Pair);
+
+        // Seven cards, and a game where every card from 2 to 4 is wild.
+        struct LowWild;
+        impl Rules for LowWild {
+            fn card_value(&self, label: char) -> Option<u8> {
+                Standard.card_value(label)
+            }
+            fn is_wild(&self, value: u8) -> bool {
+                value <= 2
+            }
+        }
+        let cards = |hand: &str| {
+            hand.chars()
+                .map(|c| Standard.card_value(c).unwrap())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(Standard.category(&cards("AAKKQQ9")), Category::TwoPair);
+        assert_eq!(LowWild.category(&cards("AAKKQQ4")), Category::FullHouse);
+        assert_eq!(LowWild.category(&cards("A2K3Q49")), Category::FourOfAKind);
+    }
+
+    #[test]
+    fn wild_substitute_test() {
+        let substitute = |hand: &str| {
+            Jokers
+                .wild_substitute(&cards(&Jokers, hand))
+                .map(|v| Jokers.card_label(v).unwrap())
+        };
+        assert_eq!(substitute("T55J5"), Some('5'));
+        assert_eq!(substitute("KTJJT"), Some('T'));
+        assert_eq!(substitute("2233J"), Some('3'));
+        assert_eq!(substitute("2345J"), Some('5'));
+        assert_eq!(substitute("JJJJJ"), Some('A'));
+        assert_eq!(substitute("32T3K"), None);
+        assert_eq!(Standard.wild_substitute(&cards(&Standard, "KTJJT")), None);
+        assert_eq!(Standard.card_label(9), Some('J'));
+        assert_eq!(Standard.card_label(13), None);
+    }
+
+    #[test]
+    fn sort_key_test() {
+        fn key(rules: &dyn Rules, hand: &str) -> u64 {
+            let cards = cards(rules, hand);
+            rules.sort_key(rules.category(&cards), &cards)
+        }
+
+        // J is above T normally but below 2 as a joker.
+        assert!(key(&Standard, "JKKK2") < key(&Standard, "QQQQ2"));
+        assert!(key(&Jokers, "JKKK2") < key(&Jokers, "QQQQ2"));
+        assert!(key(&Standard, "JJ234") > key(&Standard, "TT234"));
+        assert!(key(&Jokers, "JJ234") > key(&Jokers, "TT234"));
+        assert!(key(&Jokers, "JJ234") < key(&Jokers, "TTT34"));
+
+        // The last card still counts at the maximum hand size.
+        let a = "2".repeat(MAX_CARDS - 1) + "3";
+        let b = "2".repeat(MAX_CARDS - 1) + "4";
+        assert!(key(&Standard, &a) < key(&Standard, &b));
+    }
+}
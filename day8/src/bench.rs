@@ -0,0 +1,161 @@
+use crate::network::{Direction, Map};
+use crate::pattern::NodePattern;
+use crate::walk;
+use std::collections::HashMap;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+// The generated networks' start and end nodes. Lowercase, so no other node can match them.
+pub const START: &str = "start";
+pub const END: &str = "end";
+// Node i is named by writing i in base 26 with letters, padded to a common width.
+fn node_name(i: usize, width: usize) -> String {
+    let mut name = vec![b'A'; width];
+    let mut rest = i;
+    for c in name.iter_mut().rev() {
+        *c = b'A' + (rest % 26) as u8;
+        rest /= 26;
+    }
+    String::from_utf8(name).unwrap()
+}
+
+// A xorshift generator from a fixed seed, so generated networks are the same on every run.
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new() -> Rng {
+        Rng(0x2545f4914f6cdd1d)
+    }
+
+    // A value in 0..bound.
+    pub fn below(&mut self, bound: usize) -> usize {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        (self.0 % bound as u64) as usize
+    }
+}
+
+// Generates a network of n >= 2 nodes and a 263 step direction line. Both of a node's links go to
+// the next node on a shuffled route from START through every other node to END, so the walk
+// visits each node once, in an order that jumps around memory.
+pub fn generate(n: usize) -> Vec<String> {
+    let mut rng = Rng::new();
+    let width = (1..).find(|&w| 26usize.pow(w) >= n).unwrap().max(3) as usize;
+    let directions: String = (0..263)
+        .map(|_| if rng.below(2) == 0 { 'L' } else { 'R' })
+        .collect();
+
+    let mut route: Vec<String> = (1..n - 1).map(|i| node_name(i, width)).collect();
+    for i in (1..route.len()).rev() {
+        route.swap(i, rng.below(i + 1));
+    }
+    route.insert(0, START.to_string());
+    route.push(END.to_string());
+
+    let mut lines = vec![directions];
+    for (i, node) in route.iter().enumerate() {
+        let next = &route[(i + 1) % n];
+        lines.push(format!("{} = ({}, {})", node, next, next));
+    }
+    lines
+}
+
+// Each node's (left, right) neighbours by name, as the network was stored before interning.
+fn links_by_name(map: &Map) -> HashMap<&str, (&str, &str)> {
+    map.network
+        .names()
+        .map(|(id, name)| {
+            (
+                name,
+                (
+                    map.network.name(map.network.step(id, Direction::Left)),
+                    map.network.name(map.network.step(id, Direction::Right)),
+                ),
+            )
+        })
+        .collect()
+}
+
+// Steps from START to END, looking each one up by name.
+fn walk_by_name(map: &Map, links: &HashMap<&str, (&str, &str)>) -> u64 {
+    let mut name = START;
+    for (steps, &dir) in (1..).zip(map.directions.iter().cycle()) {
+        name = match dir {
+            Direction::Left => links[name].0,
+            Direction::Right => links[name].1,
+        };
+        if name == END {
+            return steps;
+        }
+    }
+    unreachable!()
+}
+
+pub struct Timings {
+    pub nodes: usize,
+    pub steps: u64,
+    // Parsing, including interning the node names.
+    pub parse: Duration,
+    // The part 1 walker from START to END, including its reachability check.
+    pub walk: Duration,
+    // Walking the same route through a map keyed by name, as before interning.
+    pub walk_by_name: Duration,
+}
+
+pub fn run(n: usize) -> Timings {
+    let lines = generate(n);
+    let start = NodePattern::glob(START).unwrap();
+    let end = NodePattern::glob(END).unwrap();
+
+    let timer = Instant::now();
+    let map = Map::parse(&lines).unwrap();
+    let parse = timer.elapsed();
+
+    let timer = Instant::now();
+    let steps = walk::walk(&map, &start, &end).unwrap();
+    let walk = timer.elapsed();
+
+    let links = links_by_name(&map);
+    let timer = Instant::now();
+    black_box(walk_by_name(&map, &links));
+    let walk_by_name = timer.elapsed();
+
+    Timings {
+        nodes: n,
+        steps,
+        parse,
+        walk,
+        walk_by_name,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generate_test() {
+        assert_eq!(node_name(0, 3), "AAA");
+        assert_eq!(node_name(27, 3), "ABB");
+
+        let lines = generate(1000);
+        assert_eq!(lines, generate(1000));
+        let map = Map::parse(&lines).unwrap();
+        assert_eq!(map.network.names().count(), 1000);
+        assert_eq!(map.directions.len(), 263);
+    }
+
+    #[test]
+    fn run_test() {
+        // Both walkers cross every node once.
+        let map = Map::parse(&generate(1000)).unwrap();
+        let (start, end) = (
+            NodePattern::glob(START).unwrap(),
+            NodePattern::glob(END).unwrap(),
+        );
+        assert_eq!(walk::walk(&map, &start, &end), Ok(999));
+        assert_eq!(walk_by_name(&map, &links_by_name(&map)), 999);
+        assert_eq!(run(1000).steps, 999);
+    }
+}
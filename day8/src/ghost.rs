@@ -0,0 +1,376 @@
+use crate::network::Map;
+use std::collections::BTreeSet;
+use std::fmt;
+
+// Residue sets larger than this make the CRT merge no cheaper than simulating.
+const MAX_RESIDUES: usize = 1 << 16;
+// How far the simulation fallback walks before giving up.
+const MAX_SIMULATED_STEPS: u64 = 1 << 32;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum GhostError {
+    SimulationLimit { steps: u64 },
+    TooManySteps,
+}
+
+impl fmt::Display for GhostError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GhostError::SimulationLimit { steps } => write!(
+                f,
+                "ghost cycles are too irregular to combine, and simulating {} steps found no answer",
+                steps
+            ),
+            GhostError::TooManySteps => write!(f, "the ghosts take more than 2^64 steps"),
+        }
+    }
+}
+
+// The shape of one ghost's walk. Its state is its node and position in the directions, so after
+// at most nodes * directions steps a state repeats and the walk loops from then on.
+#[derive(Debug, PartialEq, Eq)]
+pub struct GhostCycle {
+    // The step at which the walk first enters its loop.
+    pub cycle_start: u64,
+    pub cycle_len: u64,
+    // Steps before cycle_start at which the ghost is on an end node.
+    pub prefix_hits: Vec<u64>,
+    // Steps in cycle_start..cycle_start + cycle_len at which the ghost is on an end node; each
+    // recurs every cycle_len steps.
+    pub cycle_hits: Vec<u64>,
+}
+
+impl GhostCycle {
+    pub fn trace(map: &Map, start: usize, is_end: &impl Fn(usize) -> bool) -> GhostCycle {
+        // The step at which each state was first seen, indexed by node * directions + i.
+        let directions = map.directions.len();
+        let mut seen: Vec<Option<u64>> = vec![None; map.network.node_count() * directions];
+        let mut hits = vec![];
+        let mut node = start;
+        for (step, (i, &dir)) in (0..).zip(map.directions.iter().enumerate().cycle()) {
+            if let Some(cycle_start) = seen[node * directions + i].replace(step) {
+                let (prefix_hits, cycle_hits) = hits.iter().partition(|&&h| h < cycle_start);
+                return GhostCycle {
+                    cycle_start,
+                    cycle_len: step - cycle_start,
+                    prefix_hits,
+                    cycle_hits,
+                };
+            }
+
+            if is_end(node) {
+                hits.push(step);
+            }
+            node = map.network.step(node, dir);
+        }
+
+        unreachable!()
+    }
+
+    pub fn is_hit(&self, step: u64) -> bool {
+        if step < self.cycle_start {
+            self.prefix_hits.contains(&step)
+        } else {
+            let offset = (step - self.cycle_start) % self.cycle_len;
+            self.cycle_hits.contains(&(self.cycle_start + offset))
+        }
+    }
+}
+
+fn gcd(a: u128, b: u128) -> u128 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+// The inverse of a modulo m, for coprime a and m.
+fn mod_inverse(a: u128, m: u128) -> u128 {
+    let (mut old_r, mut r) = ((a % m) as i128, m as i128);
+    let (mut old_s, mut s) = (1i128, 0i128);
+    while r != 0 {
+        let q = old_r / r;
+        (old_r, r) = (r, old_r - q * r);
+        (old_s, s) = (s, old_s - q * s);
+    }
+    old_s.rem_euclid(m as i128) as u128
+}
+
+// Solves x = a1 (mod n1), x = a2 (mod n2) for moduli that need not be coprime, giving x modulo
+// lcm(n1, n2). None if the congruences conflict or the lcm overflows.
+fn crt(a1: u128, n1: u128, a2: u128, n2: u128) -> Option<Option<(u128, u128)>> {
+    let g = gcd(n1, n2);
+    let lcm = (n1 / g).checked_mul(n2)?;
+    let diff = (a2 + n2 - a1 % n2) % n2;
+    if !diff.is_multiple_of(g) {
+        return Some(None);
+    }
+
+    let m = n2 / g;
+    let k = (diff / g) % m * mod_inverse(n1 / g, m) % m;
+    Some(Some(((a1 + n1 * k) % lcm, lcm)))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Method {
+    // Found before every ghost had entered its loop.
+    Prefix,
+    Crt,
+    Simulation,
+}
+
+// The first step, at least one, at which every ghost is on an end node, or None if that never
+// happens.
+pub fn solve(
+    map: &Map,
+    starts: &[usize],
+    is_end: impl Fn(usize) -> bool,
+) -> Result<(Option<u64>, Method), GhostError> {
+    solve_with_limits(map, starts, is_end, MAX_RESIDUES, MAX_SIMULATED_STEPS)
+}
+
+fn solve_with_limits(
+    map: &Map,
+    starts: &[usize],
+    is_end: impl Fn(usize) -> bool,
+    max_residues: usize,
+    max_steps: u64,
+) -> Result<(Option<u64>, Method), GhostError> {
+    let ghosts: Vec<GhostCycle> = starts
+        .iter()
+        .map(|&s| GhostCycle::trace(map, s, &is_end))
+        .collect();
+    let Some(latest) = ghosts.iter().max_by_key(|g| g.cycle_start) else {
+        return Ok((None, Method::Prefix));
+    };
+
+    // Before the last ghost enters its loop, only its prefix hits can be answers.
+    if let Some(&step) = latest
+        .prefix_hits
+        .iter()
+        .find(|&&step| step >= 1 && ghosts.iter().all(|g| g.is_hit(step)))
+    {
+        return Ok((Some(step), Method::Prefix));
+    }
+
+    // From then on every ghost is periodic, so the answers are a union of residue classes.
+    // Merge each ghost's hits into the set of classes that work for every ghost so far.
+    let earliest = latest.cycle_start.max(1) as u128;
+    let mut modulus: u128 = 1;
+    let mut residues: BTreeSet<u128> = BTreeSet::from([0]);
+    let mut degenerate = false;
+    'merge: for ghost in &ghosts {
+        let n = ghost.cycle_len as u128;
+        let mut merged = BTreeSet::new();
+        let mut merged_modulus = modulus;
+        for &r in &residues {
+            for &hit in &ghost.cycle_hits {
+                match crt(r, modulus, hit as u128 % n, n) {
+                    Some(Some((x, lcm))) => {
+                        merged.insert(x);
+                        merged_modulus = lcm;
+                    }
+                    Some(None) => {}
+                    None => {
+                        degenerate = true;
+                        break 'merge;
+                    }
+                }
+                if merged.len() > max_residues {
+                    degenerate = true;
+                    break 'merge;
+                }
+            }
+        }
+        residues = merged;
+        modulus = merged_modulus;
+    }
+
+    if !degenerate {
+        // The first step at or after `earliest` in any of the classes.
+        let best = residues
+            .iter()
+            .map(|&r| {
+                let behind = (r + modulus - earliest % modulus) % modulus;
+                earliest + behind
+            })
+            .min();
+        return match best {
+            Some(step) if step <= u64::MAX as u128 => Ok((Some(step as u64), Method::Crt)),
+            Some(_) => Err(GhostError::TooManySteps),
+            None => Ok((None, Method::Crt)),
+        };
+    }
+
+    simulate(map, starts, is_end, max_steps).map(|steps| (steps, Method::Simulation))
+}
+
+// Walks every ghost in step until they are all on end nodes at once.
+fn simulate(
+    map: &Map,
+    starts: &[usize],
+    is_end: impl Fn(usize) -> bool,
+    max_steps: u64,
+) -> Result<Option<u64>, GhostError> {
+    let mut nodes = starts.to_vec();
+    for (step, &dir) in (1..=max_steps).zip(map.directions.iter().cycle()) {
+        for node in nodes.iter_mut() {
+            *node = map.network.step(*node, dir);
+        }
+        if nodes.iter().all(|&n| is_end(n)) {
+            return Ok(Some(step));
+        }
+    }
+
+    Err(GhostError::SimulationLimit { steps: max_steps })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bench::Rng;
+
+    fn map(text: &str) -> Map {
+        Map::parse(&text.lines().map(String::from).collect::<Vec<_>>()).unwrap()
+    }
+
+    fn ends_with(map: &Map, c: char) -> Vec<usize> {
+        map.network
+            .names()
+            .filter(|(_, n)| n.ends_with(c))
+            .map(|(id, _)| id)
+            .collect()
+    }
+
+    // Simulates far enough that every combination of cycle positions has come round.
+    fn brute_force(map: &Map, starts: &[usize], is_end: impl Fn(usize) -> bool) -> Option<u64> {
+        let ghosts: Vec<GhostCycle> = starts
+            .iter()
+            .map(|&s| GhostCycle::trace(map, s, &is_end))
+            .collect();
+        let limit = ghosts.iter().map(|g| g.cycle_start).max().unwrap()
+            + ghosts
+                .iter()
+                .fold(1, |acc, g| num::integer::lcm(acc, g.cycle_len));
+        simulate(map, starts, is_end, limit).ok().flatten()
+    }
+
+    #[test]
+    fn example_test() {
+        let map = map("LR
+11A = (11B, XXX)
+11B = (XXX, 11Z)
+11Z = (11B, XXX)
+22A = (22B, XXX)
+22B = (22C, 22C)
+22C = (22Z, 22Z)
+22Z = (22B, 22B)
+XXX = (XXX, XXX)");
+        let ends = ends_with(&map, 'Z');
+        let is_end = |n| ends.contains(&n);
+
+        let ghost = GhostCycle::trace(&map, ends_with(&map, 'A')[1], &is_end);
+        assert_eq!(
+            ghost,
+            GhostCycle {
+                cycle_start: 1,
+                cycle_len: 6,
+                prefix_hits: vec![],
+                cycle_hits: vec![3, 6],
+            }
+        );
+
+        assert_eq!(
+            solve(&map, &ends_with(&map, 'A'), is_end),
+            Ok((Some(6), Method::Crt))
+        );
+    }
+
+    #[test]
+    fn offset_cycles_test() {
+        // Ghost 1 is on its end every 3 steps from step 3, and ghost 2 every 4 steps from step 1.
+        // The LCM of the loop lengths is 12, but the first common step is 9.
+        let map = map("L
+1A = (1B, 1B)
+1B = (1C, 1C)
+1C = (1Z, 1Z)
+1Z = (1B, 1B)
+2A = (2Z, 2Z)
+2Z = (2B, 2B)
+2B = (2C, 2C)
+2C = (2D, 2D)
+2D = (2Z, 2Z)");
+        let ends = ends_with(&map, 'Z');
+        let starts = ends_with(&map, 'A');
+        let is_end = |n| ends.contains(&n);
+        assert_eq!(solve(&map, &starts, is_end), Ok((Some(9), Method::Crt)));
+        assert_eq!(brute_force(&map, &starts, is_end), Some(9));
+
+        // Loops of 3 and 6 whose hits never line up: steps 1, 4, 7.. against 3, 9, 15..
+        let map = self::map(
+            "L
+1A = (1Z, 1Z)
+1Z = (1B, 1B)
+1B = (1C, 1C)
+1C = (1Z, 1Z)
+2A = (2B, 2B)
+2B = (2C, 2C)
+2C = (2Z, 2Z)
+2Z = (2D, 2D)
+2D = (2E, 2E)
+2E = (2F, 2F)
+2F = (2B, 2B)",
+        );
+        let ends = ends_with(&map, 'Z');
+        let is_end = |n| ends.contains(&n);
+        assert_eq!(
+            solve(&map, &ends_with(&map, 'A'), is_end),
+            Ok((None, Method::Crt))
+        );
+    }
+
+    #[test]
+    fn random_networks_test() {
+        let mut rng = Rng::new();
+        let mut next = |bound: usize| rng.below(bound);
+
+        for _ in 0..100 {
+            let nodes = 2 + next(8);
+            let mut lines = vec![(0..1 + next(4))
+                .map(|_| if next(2) == 0 { 'L' } else { 'R' })
+                .collect::<String>()];
+            for i in 0..nodes {
+                lines.push(format!("N{} = (N{}, N{})", i, next(nodes), next(nodes)));
+            }
+            let map = Map::parse(&lines).unwrap();
+
+            let ends: Vec<usize> = (0..nodes).filter(|_| next(3) == 0).collect();
+            let starts: Vec<usize> = (0..1 + next(3)).map(|_| next(nodes)).collect();
+            let is_end = |n| ends.contains(&n);
+
+            let expected = brute_force(&map, &starts, is_end);
+            let (steps, _) = solve(&map, &starts, is_end).unwrap();
+            assert_eq!(
+                steps, expected,
+                "{:?} starts {:?} ends {:?}",
+                lines, starts, ends
+            );
+
+            // Forcing the fallback gives the same answer whenever one exists.
+            if expected.is_some() {
+                let (steps, _) = solve_with_limits(&map, &starts, is_end, 0, 100_000).unwrap();
+                assert_eq!(steps, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn crt_test() {
+        assert_eq!(crt(2, 3, 3, 5), Some(Some((8, 15))));
+        assert_eq!(crt(1, 4, 3, 6), Some(Some((9, 12))));
+        assert_eq!(crt(0, 4, 1, 6), Some(None));
+        assert_eq!(crt(0, 1, 5, 7), Some(Some((5, 7))));
+        assert_eq!(crt(0, u128::MAX, 0, u128::MAX - 1), None);
+    }
+}
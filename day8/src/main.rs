@@ -1,95 +1,138 @@
-
-use num;
-use regex::Regex;
-use std::collections::HashMap;
+mod bench;
+mod ghost;
+mod network;
+mod pattern;
+mod walk;
+
+use network::Map;
+use pattern::NodePattern;
 use std::fs::File;
 use std::io::{BufRead, BufReader};
+use walk::WalkError;
 
-fn parse_input(lines: &Vec<String>) -> (String, HashMap<String, (String, String)>) {
-    let re = Regex::new(r"(?<node>\w+) = \((?<left>\w+), (?<right>\w+)\)").unwrap();
+fn part1(map: &Map) -> Result<u64, WalkError> {
+    walk::walk(map, &glob(PART1_START), &glob(PART1_END))
+}
 
-    let directions: String = lines[0].clone();
-    let mut network: HashMap<String, (String, String)> = HashMap::new();
+fn part2(map: &Map) -> Result<Option<u64>, WalkError> {
+    let (steps, _) = walk::ghost_walk(map, &glob(PART2_START), &glob(PART2_END))?;
+    Ok(steps)
+}
 
-    for line in lines {
-        if let Some(caps) = re.captures(line) {
-            network.insert(
-                caps["node"].to_string(),
-                (caps["left"].to_string(), caps["right"].to_string()),
-            );
-        }
-    }
+// The puzzle's start and end nodes. Part 2 uses every node whose third character is A or Z.
+const PART1_START: &str = "AAA";
+const PART1_END: &str = "ZZZ";
+const PART2_START: &str = "??A*";
+const PART2_END: &str = "??Z*";
 
-    return (directions, network);
+fn glob(pattern: &str) -> NodePattern {
+    NodePattern::glob(pattern).unwrap()
 }
 
-fn part1(lines: &Vec<String>) -> u32 {
-    let (directions, network) = parse_input(lines);
+fn exit_with(err: impl std::fmt::Display) -> ! {
+    eprintln!("{}", err);
+    std::process::exit(1);
+}
 
-    let mut cur_node = "AAA";
-    let mut steps = 1;
-    for dir in directions.chars().cycle() {
-        if dir == 'L' {
-            cur_node = &network[cur_node].0;
-        } else {
-            cur_node = &network[cur_node].1;
-        }
+const USAGE: &str =
+    "usage: day8 [--input FILE] [--start PATTERN] [--end PATTERN] [--regex] [COMMAND]
 
-        if cur_node == "ZZZ" {
-            break;
-        }
+  (none)         print the part 1 and part 2 answers
+  walk           count the steps from the node matching --start to the first node matching
+                 --end (default AAA to ZZZ)
+  ghosts         count the steps until ghosts starting on every node matching --start are all
+                 on nodes matching --end at once (default ??A* to ??Z*)
+  bench [N..]    time parsing a generated network of N >= 2 nodes, for each N given (default
+                 1000 100000 1000000), and walking it from start to end through every node
 
-        steps += 1;
-    }
+Patterns are globs matching whole node names, with * ? and [..]; with --regex they are
+regular expressions, still matching whole names.";
 
-    return steps;
+fn read_map(input: &str) -> Map {
+    let file = File::open(input).unwrap_or_else(|err| exit_with(format!("{}: {}", input, err)));
+    let reader = BufReader::new(file);
+    let lines: Vec<String> = reader.lines().map(|l| l.unwrap()).collect();
+    Map::parse(&lines).unwrap_or_else(|err| exit_with(err))
 }
 
-fn find_loop_length(
-    start: &String,
-    directions: &String,
-    network: &HashMap<String, (String, String)>,
-) -> u32 {
-    let mut cur_node = start;
-    let mut visited: HashMap<(String, usize), u32> = HashMap::new();
-    let mut steps = 1;
-    for (i, dir) in directions.chars().enumerate().cycle() {
-        if let Some(last_steps) = visited.get(&(cur_node.clone(), i)) {
-            return steps - last_steps;
-        } else {
-            visited.insert((cur_node.to_string(), i), steps);
+fn main() {
+    let mut input = String::from("input");
+    let mut start: Option<String> = None;
+    let mut end: Option<String> = None;
+    let mut regex = false;
+    let mut command: Vec<String> = vec![];
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().unwrap_or_else(|| exit_with(USAGE));
+        match arg.as_str() {
+            "--input" => input = value(),
+            "--start" => start = Some(value()),
+            "--end" => end = Some(value()),
+            "--regex" => regex = true,
+            "-h" | "--help" => exit_with(USAGE),
+            _ => command.push(arg),
         }
-
-        if dir == 'L' {
-            cur_node = &network[cur_node].0;
-        } else {
-            cur_node = &network[cur_node].1;
+    }
+    let pattern = |given: &Option<String>, default: &str| match given {
+        Some(p) if regex => NodePattern::regex(p).unwrap_or_else(|err| exit_with(err)),
+        Some(p) => NodePattern::glob(p).unwrap_or_else(|err| exit_with(err)),
+        None => glob(default),
+    };
+
+    let command: Vec<&str> = command.iter().map(|s| s.as_str()).collect();
+    match command.as_slice() {
+        [] => {
+            let map = read_map(&input);
+            let pt1_result = part1(&map).unwrap_or_else(|err| exit_with(err));
+            let pt2_result = part2(&map)
+                .unwrap_or_else(|err| exit_with(err))
+                .unwrap_or_else(|| exit_with("the ghosts are never all on Z nodes at once"));
+
+            println!("Part 1: {}, Part 2: {}", pt1_result, pt2_result);
         }
-        steps += 1;
+        ["walk"] => {
+            let map = read_map(&input);
+            let (start, end) = (pattern(&start, PART1_START), pattern(&end, PART1_END));
+            let steps = walk::walk(&map, &start, &end).unwrap_or_else(|err| exit_with(err));
+            println!("{} steps", steps);
+        }
+        ["ghosts"] => {
+            let map = read_map(&input);
+            let (start, end) = (pattern(&start, PART2_START), pattern(&end, PART2_END));
+            let ghosts = start.select(&map.network).len();
+            match walk::ghost_walk(&map, &start, &end).unwrap_or_else(|err| exit_with(err)) {
+                (Some(steps), method) => {
+                    println!("{} ghosts: {} steps (found by {:?})", ghosts, steps, method)
+                }
+                (None, _) => println!(
+                    "{} ghosts: never all on nodes matching '{}' at once",
+                    ghosts,
+                    end.source()
+                ),
+            }
+        }
+        ["bench", sizes @ ..] => {
+            let sizes: Vec<usize> = if sizes.is_empty() {
+                vec![1_000, 100_000, 1_000_000]
+            } else {
+                sizes
+                    .iter()
+                    .map(|s| match s.parse() {
+                        Ok(n) if n >= 2 => n,
+                        _ => exit_with(USAGE),
+                    })
+                    .collect()
+            };
+            for n in sizes {
+                let timings = bench::run(n);
+                println!(
+                    "{:>9} nodes: parse {:>10.2?}, {} steps walker {:>10.2?}, by name {:>10.2?}",
+                    timings.nodes, timings.parse, timings.steps, timings.walk, timings.walk_by_name
+                );
+            }
+        }
+        _ => exit_with(USAGE),
     }
-
-    return 0;
-}
-
-fn part2(lines: &Vec<String>) -> u64 {
-    let (directions, network) = parse_input(lines);
-    let loop_lengths: Vec<u64> = network
-        .keys()
-        .filter(|n| n.chars().nth(2).unwrap() == 'A')
-        .map(|s| find_loop_length(s, &directions, &network) as u64)
-        .collect();
-    return loop_lengths.iter().fold(1, |acc, &l| num::integer::lcm(acc, l));
-}
-
-fn main() {
-    let file = File::open("input").unwrap();
-    let reader = BufReader::new(file);
-    let lines: Vec<String> = reader.lines().map(|l| l.unwrap()).collect();
-
-    let pt1_result = part1(&lines);
-    let pt2_result = part2(&lines);
-
-    println!("Part 1: {}, Part 2: {}", pt1_result, pt2_result);
 }
 
 #[cfg(test)]
@@ -109,8 +152,8 @@ mod tests {
             String::from("ZZZ = (ZZZ, ZZZ)"),
         ];
 
-        let result = part1(&lines);
-        assert_eq!(result, 2);
+        let result = part1(&Map::parse(&lines).unwrap());
+        assert_eq!(result, Ok(2));
     }
 
     #[test]
@@ -122,8 +165,8 @@ mod tests {
             String::from("ZZZ = (ZZZ, ZZZ)"),
         ];
 
-        let result = part1(&lines);
-        assert_eq!(result, 6);
+        let result = part1(&Map::parse(&lines).unwrap());
+        assert_eq!(result, Ok(6));
     }
 
     #[test]
@@ -140,7 +183,7 @@ mod tests {
             String::from("XXX = (XXX, XXX)"),
         ];
 
-        let result = part2(&lines);
-        assert_eq!(result, 6);
+        let result = part2(&Map::parse(&lines).unwrap());
+        assert_eq!(result, Ok(Some(6)));
     }
 }
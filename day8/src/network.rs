@@ -0,0 +1,255 @@
+use regex::Regex;
+use std::collections::HashMap;
+use std::fmt;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Direction {
+    Left = 0,
+    Right = 1,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ParseError {
+    Empty,
+    BadDirection { col: usize, label: char },
+    Malformed { line: usize },
+    DuplicateNode { line: usize, name: String },
+    UndefinedNode { name: String, line: usize },
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::Empty => write!(f, "line 1: no directions"),
+            ParseError::BadDirection { col, label } => write!(
+                f,
+                "line 1, column {}: '{}' is not a direction, expected L or R",
+                col, label
+            ),
+            ParseError::Malformed { line } => {
+                write!(f, "line {}: expected 'NODE = (LEFT, RIGHT)'", line)
+            }
+            ParseError::DuplicateNode { line, name } => {
+                write!(f, "line {}: node {} is defined twice", line, name)
+            }
+            ParseError::UndefinedNode { name, line } => write!(
+                f,
+                "line {}: links to node {}, which is never defined",
+                line, name
+            ),
+        }
+    }
+}
+
+// The node names are interned to dense ids in order of first appearance, and each node's
+// neighbours are stored side by side, so a step is a single index into `links`.
+#[derive(Debug)]
+pub struct Network {
+    names: Vec<String>,
+    // links[2 * id] is the node's left neighbour and links[2 * id + 1] its right.
+    links: Vec<u32>,
+}
+
+impl Network {
+    pub fn name(&self, id: usize) -> &str {
+        &self.names[id]
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = (usize, &str)> {
+        self.names.iter().map(|n| n.as_str()).enumerate()
+    }
+
+    pub fn step(&self, node: usize, dir: Direction) -> usize {
+        self.links[2 * node + dir as usize] as usize
+    }
+
+    // Whether each node can be reached from `start` by some sequence of left and right steps,
+    // indexed by node id. The start itself counts only if the walk can return to it.
+    pub fn reachable(&self, start: usize) -> Vec<bool> {
+        let mut reached = vec![false; self.names.len()];
+        let mut stack = vec![start];
+        while let Some(node) = stack.pop() {
+            for dir in [Direction::Left, Direction::Right] {
+                let next = self.step(node, dir);
+                if !reached[next] {
+                    reached[next] = true;
+                    stack.push(next);
+                }
+            }
+        }
+        reached
+    }
+}
+
+#[derive(Debug)]
+pub struct Map {
+    pub directions: Vec<Direction>,
+    pub network: Network,
+}
+
+impl Map {
+    pub fn parse(lines: &[String]) -> Result<Map, ParseError> {
+        let re = Regex::new(r"^(?<node>\w+) = \((?<left>\w+), (?<right>\w+)\)$").unwrap();
+
+        let directions = lines
+            .first()
+            .ok_or(ParseError::Empty)?
+            .trim()
+            .chars()
+            .enumerate()
+            .map(|(i, c)| match c {
+                'L' => Ok(Direction::Left),
+                'R' => Ok(Direction::Right),
+                _ => Err(ParseError::BadDirection {
+                    col: i + 1,
+                    label: c,
+                }),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        if directions.is_empty() {
+            return Err(ParseError::Empty);
+        }
+
+        let mut names: Vec<String> = vec![];
+        let mut ids: HashMap<String, usize> = HashMap::new();
+        let mut intern = |name: &str| {
+            *ids.entry(name.to_string()).or_insert_with(|| {
+                names.push(name.to_string());
+                names.len() - 1
+            })
+        };
+
+        // (node, left, right) for each definition, with the line it was on.
+        let mut definitions: Vec<(usize, usize, usize, usize)> = vec![];
+        for (i, line) in lines.iter().enumerate().skip(1) {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            let caps = re
+                .captures(line)
+                .ok_or(ParseError::Malformed { line: i + 1 })?;
+            definitions.push((
+                i + 1,
+                intern(&caps["node"]),
+                intern(&caps["left"]),
+                intern(&caps["right"]),
+            ));
+        }
+
+        const UNDEFINED: u32 = u32::MAX;
+        let mut links = vec![UNDEFINED; 2 * names.len()];
+        for &(line, node, left, right) in &definitions {
+            if links[2 * node] != UNDEFINED {
+                return Err(ParseError::DuplicateNode {
+                    line,
+                    name: names[node].clone(),
+                });
+            }
+            links[2 * node] = left as u32;
+            links[2 * node + 1] = right as u32;
+        }
+        // Report the first link, in line order, to a node with no definition.
+        for &(line, _, left, right) in &definitions {
+            if let Some(node) = [left, right]
+                .into_iter()
+                .find(|&n| links[2 * n] == UNDEFINED)
+            {
+                return Err(ParseError::UndefinedNode {
+                    name: names[node].clone(),
+                    line,
+                });
+            }
+        }
+
+        Ok(Map {
+            directions,
+            network: Network { names, links },
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(text: &str) -> Vec<String> {
+        text.lines().map(String::from).collect()
+    }
+
+    fn id(network: &Network, name: &str) -> Option<usize> {
+        network.names().find(|&(_, n)| n == name).map(|(id, _)| id)
+    }
+
+    #[test]
+    fn parse_test() {
+        let map = Map::parse(&lines(
+            "LLR\n\nAAA = (BBB, BBB)\nBBB = (AAA, ZZZ)\nZZZ = (ZZZ, ZZZ)",
+        ))
+        .unwrap();
+        assert_eq!(
+            map.directions,
+            vec![Direction::Left, Direction::Left, Direction::Right]
+        );
+
+        let network = &map.network;
+        assert_eq!(network.names().count(), 3);
+        let (a, b, z) = (
+            id(network, "AAA").unwrap(),
+            id(network, "BBB").unwrap(),
+            id(network, "ZZZ").unwrap(),
+        );
+        assert_eq!(network.step(a, Direction::Left), b);
+        assert_eq!(network.step(b, Direction::Left), a);
+        assert_eq!(network.step(b, Direction::Right), z);
+        assert_eq!(network.name(z), "ZZZ");
+        assert_eq!(id(network, "CCC"), None);
+    }
+
+    #[test]
+    fn reachable_test() {
+        let map = Map::parse(&lines(
+            "L\nAAA = (BBB, BBB)\nBBB = (CCC, BBB)\nCCC = (BBB, BBB)\nDDD = (AAA, AAA)",
+        ))
+        .unwrap();
+        let network = &map.network;
+        let reached = |name| network.reachable(id(network, name).unwrap());
+        assert_eq!(reached("AAA"), vec![false, true, true, false]);
+        assert_eq!(reached("DDD"), vec![true, true, true, false]);
+        assert_eq!(reached("BBB"), vec![false, true, true, false]);
+    }
+
+    #[test]
+    fn parse_errors_test() {
+        assert_eq!(
+            Map::parse(&lines("\nAAA = (AAA, AAA)")).unwrap_err(),
+            ParseError::Empty
+        );
+        assert_eq!(
+            Map::parse(&lines("LXR\nAAA = (AAA, AAA)")).unwrap_err(),
+            ParseError::BadDirection { col: 2, label: 'X' }
+        );
+        assert_eq!(
+            Map::parse(&lines("LR\nAAA = (AAA)")).unwrap_err(),
+            ParseError::Malformed { line: 2 }
+        );
+        assert_eq!(
+            Map::parse(&lines("LR\nAAA = (AAA, AAA)\nAAA = (BBB, BBB)")).unwrap_err(),
+            ParseError::DuplicateNode {
+                line: 3,
+                name: String::from("AAA")
+            }
+        );
+        assert_eq!(
+            Map::parse(&lines("LR\nAAA = (AAA, BBB)")).unwrap_err(),
+            ParseError::UndefinedNode {
+                name: String::from("BBB"),
+                line: 2
+            }
+        );
+    }
+}
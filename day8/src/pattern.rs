@@ -0,0 +1,136 @@
+use crate::network::Network;
+use regex::Regex;
+use std::fmt;
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct PatternError {
+    pub pattern: String,
+    pub message: String,
+}
+
+impl fmt::Display for PatternError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "bad pattern '{}': {}", self.pattern, self.message)
+    }
+}
+
+// Selects nodes by name. Both globs and regexes must match the whole name.
+#[derive(Debug, Clone)]
+pub struct NodePattern {
+    source: String,
+    regex: Regex,
+}
+
+impl NodePattern {
+    pub fn regex(pattern: &str) -> Result<NodePattern, PatternError> {
+        Regex::new(&format!("^(?:{})$", pattern))
+            .map(|regex| NodePattern {
+                source: pattern.to_string(),
+                regex,
+            })
+            .map_err(|err| PatternError {
+                pattern: pattern.to_string(),
+                message: err.to_string(),
+            })
+    }
+
+    // Supports `*` for any run of characters, `?` for any one character and `[..]` classes,
+    // negated with a leading `!`.
+    pub fn glob(pattern: &str) -> Result<NodePattern, PatternError> {
+        let mut translated = String::new();
+        let mut chars = pattern.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '*' => translated.push_str(".*"),
+                '?' => translated.push('.'),
+                '[' => {
+                    translated.push('[');
+                    let mut class = chars.by_ref().take_while(|&c| c != ']').peekable();
+                    if class.next_if_eq(&'!').is_some() {
+                        translated.push('^');
+                    }
+                    for c in class {
+                        if matches!(c, '[' | '\\' | '^' | '&' | '~') {
+                            translated.push('\\');
+                        }
+                        translated.push(c);
+                    }
+                    translated.push(']');
+                }
+                _ => translated.push_str(&regex::escape(&c.to_string())),
+            }
+        }
+
+        let mut glob = NodePattern::regex(&translated).map_err(|err| PatternError {
+            pattern: pattern.to_string(),
+            message: err.message,
+        })?;
+        glob.source = pattern.to_string();
+        Ok(glob)
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    pub fn matches(&self, name: &str) -> bool {
+        self.regex.is_match(name)
+    }
+
+    // Whether each node matches, indexed by node id.
+    pub fn mask(&self, network: &Network) -> Vec<bool> {
+        network
+            .names()
+            .map(|(_, name)| self.matches(name))
+            .collect()
+    }
+
+    // The ids of the matching nodes, in id order.
+    pub fn select(&self, network: &Network) -> Vec<usize> {
+        network
+            .names()
+            .filter(|(_, name)| self.matches(name))
+            .map(|(id, _)| id)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn glob_test() {
+        let glob = NodePattern::glob("??A*").unwrap();
+        assert!(glob.matches("11A"));
+        assert!(glob.matches("XYAB"));
+        assert!(!glob.matches("AA"));
+        assert!(!glob.matches("ABC"));
+
+        let glob = NodePattern::glob("[A-C!]?.").unwrap();
+        assert!(glob.matches("BX."));
+        assert!(glob.matches("!X."));
+        assert!(!glob.matches("BXY"));
+        assert!(!glob.matches("DX."));
+
+        let glob = NodePattern::glob("[!Z]*").unwrap();
+        assert!(glob.matches("AZZ"));
+        assert!(!glob.matches("ZAA"));
+
+        assert_eq!(glob.source(), "[!Z]*");
+        assert!(NodePattern::glob("[").is_err());
+    }
+
+    #[test]
+    fn regex_test() {
+        let regex = NodePattern::regex("A+|Z{3}").unwrap();
+        assert!(regex.matches("AAA"));
+        assert!(regex.matches("ZZZ"));
+        assert!(!regex.matches("AAZ"));
+
+        assert_eq!(
+            NodePattern::regex("(").unwrap_err().pattern,
+            String::from("(")
+        );
+    }
+}
@@ -0,0 +1,253 @@
+use crate::ghost::{self, GhostError, Method};
+use crate::network::Map;
+use crate::pattern::NodePattern;
+use std::fmt;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum WalkError {
+    NoStart {
+        pattern: String,
+    },
+    AmbiguousStart {
+        pattern: String,
+        matches: Vec<String>,
+    },
+    NoEnd {
+        pattern: String,
+    },
+    // No sequence of steps at all leads from the start to an end node.
+    Unreachable {
+        start: String,
+        end: String,
+    },
+    // The walk returned to the same node at the start of the directions without passing an end
+    // node, so it will repeat forever.
+    NeverArrives {
+        start: String,
+        end: String,
+        cycle_start: u64,
+        cycle_len: u64,
+    },
+    Ghost(GhostError),
+}
+
+impl fmt::Display for WalkError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            WalkError::NoStart { pattern } => write!(f, "no node matches start '{}'", pattern),
+            WalkError::AmbiguousStart { pattern, matches } => write!(
+                f,
+                "start '{}' matches {} nodes ({}), but a single walker needs exactly one",
+                pattern,
+                matches.len(),
+                matches.join(", ")
+            ),
+            WalkError::NoEnd { pattern } => write!(f, "no node matches end '{}'", pattern),
+            WalkError::Unreachable { start, end } => write!(
+                f,
+                "no node matching '{}' can be reached from {}",
+                end, start
+            ),
+            WalkError::NeverArrives {
+                start,
+                end,
+                cycle_start,
+                cycle_len,
+            } => write!(
+                f,
+                "the walk from {} loops every {} steps from step {} without reaching a node \
+                 matching '{}'",
+                start, cycle_len, cycle_start, end
+            ),
+            WalkError::Ghost(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl From<GhostError> for WalkError {
+    fn from(err: GhostError) -> Self {
+        WalkError::Ghost(err)
+    }
+}
+
+fn starts(map: &Map, start: &NodePattern) -> Result<Vec<usize>, WalkError> {
+    let starts = start.select(&map.network);
+    if starts.is_empty() {
+        return Err(WalkError::NoStart {
+            pattern: start.source().to_string(),
+        });
+    }
+    Ok(starts)
+}
+
+// Which nodes match `end`, checking that some can be reached from every start.
+fn ends(map: &Map, starts: &[usize], end: &NodePattern) -> Result<Vec<bool>, WalkError> {
+    let is_end = end.mask(&map.network);
+    if !is_end.contains(&true) {
+        return Err(WalkError::NoEnd {
+            pattern: end.source().to_string(),
+        });
+    }
+
+    for &start in starts {
+        let reachable = map.network.reachable(start);
+        if !reachable.iter().zip(&is_end).any(|(&r, &e)| r && e) {
+            return Err(WalkError::Unreachable {
+                start: map.network.name(start).to_string(),
+                end: end.source().to_string(),
+            });
+        }
+    }
+    Ok(is_end)
+}
+
+// Steps for one walker from the node matching `start` to the first node matching `end`.
+pub fn walk(map: &Map, start: &NodePattern, end: &NodePattern) -> Result<u64, WalkError> {
+    let starts = starts(map, start)?;
+    let [first] = starts[..] else {
+        return Err(WalkError::AmbiguousStart {
+            pattern: start.source().to_string(),
+            matches: starts
+                .iter()
+                .map(|&id| map.network.name(id).to_string())
+                .collect(),
+        });
+    };
+    let is_end = ends(map, &starts, end)?;
+
+    // The step at which each node was first seen at the start of the directions. Any loop passes
+    // through the start of the directions, so checking there alone catches it within one pass.
+    let mut seen: Vec<Option<u64>> = vec![None; map.network.node_count()];
+    let mut cur_node = first;
+    for (steps, (i, &dir)) in (1..).zip(map.directions.iter().enumerate().cycle()) {
+        if i == 0 {
+            if let Some(first_seen) = seen[cur_node].replace(steps - 1) {
+                return Err(WalkError::NeverArrives {
+                    start: map.network.name(first).to_string(),
+                    end: end.source().to_string(),
+                    cycle_start: first_seen,
+                    cycle_len: steps - 1 - first_seen,
+                });
+            }
+        }
+
+        cur_node = map.network.step(cur_node, dir);
+        if is_end[cur_node] {
+            return Ok(steps);
+        }
+    }
+
+    unreachable!()
+}
+
+// Steps until ghosts starting on every node matching `start` are all on nodes matching `end`,
+// or None if that never happens.
+pub fn ghost_walk(
+    map: &Map,
+    start: &NodePattern,
+    end: &NodePattern,
+) -> Result<(Option<u64>, Method), WalkError> {
+    let starts = starts(map, start)?;
+    let is_end = ends(map, &starts, end)?;
+    Ok(ghost::solve(map, &starts, |id| is_end[id])?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn map(text: &str) -> Map {
+        Map::parse(&text.lines().map(String::from).collect::<Vec<_>>()).unwrap()
+    }
+
+    #[test]
+    fn walk_test() {
+        let map = map("LLR
+AAA = (BBB, BBB)
+BBB = (AAA, ZZZ)
+ZZZ = (ZZZ, ZZZ)");
+        let glob = |p| NodePattern::glob(p).unwrap();
+
+        assert_eq!(walk(&map, &glob("AAA"), &glob("ZZZ")), Ok(6));
+        assert_eq!(walk(&map, &glob("AAA"), &glob("B*")), Ok(1));
+        assert_eq!(
+            walk(&map, &NodePattern::regex("A.A").unwrap(), &glob("[BZ]ZZ")),
+            Ok(6)
+        );
+        assert_eq!(
+            walk(&map, &glob("?X?"), &glob("ZZZ")),
+            Err(WalkError::NoStart {
+                pattern: String::from("?X?")
+            })
+        );
+        assert_eq!(
+            walk(&map, &glob("[AB]*"), &glob("ZZZ")),
+            Err(WalkError::AmbiguousStart {
+                pattern: String::from("[AB]*"),
+                matches: vec![String::from("AAA"), String::from("BBB")]
+            })
+        );
+
+        assert_eq!(
+            walk(&map, &glob("AAA"), &glob("CCC")),
+            Err(WalkError::NoEnd {
+                pattern: String::from("CCC")
+            })
+        );
+
+        // Ghosts accept the same patterns, and a lone ghost agrees with the single walker.
+        assert_eq!(
+            ghost_walk(&map, &glob("AAA"), &glob("ZZZ")).unwrap().0,
+            Some(6)
+        );
+        assert_eq!(
+            ghost_walk(&map, &glob("[AB]*"), &glob("ZZZ")).unwrap().0,
+            Some(6)
+        );
+    }
+
+    #[test]
+    fn non_termination_test() {
+        let map = map("LR
+AAA = (BBB, CCC)
+BBB = (AAA, ZZZ)
+CCC = (AAA, AAA)
+ZZZ = (ZZZ, ZZZ)
+YYY = (ZZZ, ZZZ)");
+        let glob = |p| NodePattern::glob(p).unwrap();
+
+        // ZZZ can be reached from CCC through AAA and BBB, but the directions only ever take CCC
+        // left to AAA and then AAA right back to CCC.
+        assert_eq!(
+            walk(&map, &glob("CCC"), &glob("ZZZ")),
+            Err(WalkError::NeverArrives {
+                start: String::from("CCC"),
+                end: String::from("ZZZ"),
+                cycle_start: 0,
+                cycle_len: 2
+            })
+        );
+        assert_eq!(walk(&map, &glob("AAA"), &glob("ZZZ")), Ok(2));
+
+        assert_eq!(
+            walk(&map, &glob("ZZZ"), &glob("YYY")),
+            Err(WalkError::Unreachable {
+                start: String::from("ZZZ"),
+                end: String::from("YYY")
+            })
+        );
+        assert_eq!(
+            ghost_walk(&map, &glob("[AZ]*"), &glob("BBB")),
+            Err(WalkError::Unreachable {
+                start: String::from("ZZZ"),
+                end: String::from("BBB")
+            })
+        );
+        assert_eq!(
+            walk(&map, &glob("ZZZ"), &glob("YYY"))
+                .unwrap_err()
+                .to_string(),
+            "no node matching 'YYY' can be reached from ZZZ"
+        );
+    }
+}